@@ -0,0 +1,76 @@
+use rocket::form::Form;
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::Redirect;
+use rocket::State;
+
+use crate::config::AppConfig;
+
+// Cookie holding the admin token once a browser is unlocked. SameSite=Strict keeps other
+// sites from posting forms with it.
+const COOKIE: &str = "admin_token";
+
+// Guard for routes that change state shared by every reader, like saved searches and
+// categories. Passes with the configured `admin_token`, sent as a bearer token or in the
+// cookie set by /admin/unlock; all such routes are disabled when no token is configured.
+pub struct Admin;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, &'static str> {
+        let expected = match request.guard::<&State<AppConfig>>().await {
+            Outcome::Success(config) => config.admin_token.clone().filter(|token| !token.is_empty()),
+            _ => None,
+        };
+        let Some(expected) = expected else {
+            return Outcome::Error((Status::Forbidden, "Admin routes are disabled"));
+        };
+        let cookie = request.cookies().get(COOKIE).map(|cookie| cookie.value());
+        if bearer_matches(request, &expected) || cookie.is_some_and(|given| tokens_match(given, &expected)) {
+            Outcome::Success(Admin)
+        } else {
+            Outcome::Error((Status::Unauthorized, "Missing or wrong admin token"))
+        }
+    }
+}
+
+// Whether the request's `Authorization: Bearer` header carries `expected`
+pub fn bearer_matches(request: &Request<'_>, expected: &str) -> bool {
+    request
+        .headers()
+        .get_one("Authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| tokens_match(given.trim(), expected))
+}
+
+// Compare in time independent of where the tokens differ
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[derive(Debug, FromForm)]
+pub struct Unlock {
+    token: String,
+    // Where to go afterwards; only paths on this site
+    next: Option<String>,
+}
+
+// Remember the admin token in this browser so admin forms work without a bearer header
+#[post("/admin/unlock", data = "<form>")]
+pub async fn unlock(form: Form<Unlock>, cookies: &CookieJar<'_>, config: &State<AppConfig>) -> Result<Redirect, Status> {
+    let expected = config.admin_token.as_deref().filter(|token| !token.is_empty()).ok_or(Status::Forbidden)?;
+    if !tokens_match(form.token.trim(), expected) {
+        return Err(Status::Unauthorized);
+    }
+    cookies.add(
+        Cookie::build((COOKIE, form.token.trim().to_string()))
+            .path("/")
+            .http_only(true)
+            .same_site(SameSite::Strict),
+    );
+    let next = form.next.as_deref().filter(|next| next.starts_with('/') && !next.starts_with("//")).unwrap_or("/");
+    Ok(Redirect::to(next.to_string()))
+}
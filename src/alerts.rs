@@ -0,0 +1,254 @@
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::ids::{permalink, stable_hash};
+use crate::json_file::JsonFile;
+use crate::search::SearchIndex;
+use crate::store::ArticleStore;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelFormat {
+    // The alerts as JSON, for generic webhooks
+    #[default]
+    Json,
+    // A `{"text": ...}` message, understood by Slack and Mattermost incoming webhooks
+    Slack,
+}
+
+// An outbound destination for alerts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub format: ChannelFormat,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AlertsConfig {
+    pub channels: Vec<ChannelConfig>,
+    // Alerts kept in the inbox; the oldest are dropped first
+    pub max_alerts: usize,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        AlertsConfig {
+            channels: Vec::new(),
+            max_alerts: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub id: String,
+    pub name: String,
+    // In the search syntax, e.g. `rust -source:medium`
+    pub query: String,
+    // Names of configured channels to deliver matches to
+    #[serde(default)]
+    pub channels: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// A new article that matched a saved search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub search_id: String,
+    pub article_id: String,
+    pub title: String,
+    pub url: String,
+    pub source: String,
+    pub matched_at: DateTime<Utc>,
+    #[serde(default)]
+    pub read: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AlertData {
+    searches: Vec<SavedSearch>,
+    // Newest first
+    alerts: Vec<Alert>,
+}
+
+// Saved searches and the alerts they raised, persisted as JSON.
+// Cloning is cheap: clones share the same data.
+#[derive(Clone)]
+pub struct AlertStore {
+    data: Arc<RwLock<AlertData>>,
+    file: JsonFile<AlertData>,
+    max_alerts: usize,
+}
+
+impl AlertStore {
+    // Load saved searches and alerts from disk, starting empty if the file is missing or unreadable
+    pub fn open(path: PathBuf, config: &AlertsConfig) -> Self {
+        let file = JsonFile::new(path, "alerts");
+        let data = file.load().unwrap_or_default();
+
+        AlertStore {
+            data: Arc::new(RwLock::new(data)),
+            file,
+            max_alerts: config.max_alerts,
+        }
+    }
+
+    pub fn searches(&self) -> Vec<SavedSearch> {
+        self.data.read().unwrap().searches.clone()
+    }
+
+    pub fn search(&self, id: &str) -> Option<SavedSearch> {
+        self.data.read().unwrap().searches.iter().find(|search| search.id == id).cloned()
+    }
+
+    pub fn add_search(&self, name: &str, query: &str, channels: Vec<String>) -> SavedSearch {
+        let created_at = Utc::now();
+        let saved = SavedSearch {
+            id: format!("{:016x}", stable_hash(&format!("{}\n{}\n{}", name, query, created_at.to_rfc3339()))),
+            name: name.trim().to_string(),
+            query: query.trim().to_string(),
+            channels,
+            created_at,
+        };
+        self.data.write().unwrap().searches.push(saved.clone());
+        self.save();
+        saved
+    }
+
+    // Forget a saved search along with its alerts
+    pub fn delete_search(&self, id: &str) -> bool {
+        let mut data = self.data.write().unwrap();
+        let before = data.searches.len();
+        data.searches.retain(|search| search.id != id);
+        data.alerts.retain(|alert| alert.search_id != id);
+        let deleted = data.searches.len() != before;
+        drop(data);
+        if deleted {
+            self.save();
+        }
+        deleted
+    }
+
+    // Alerts newest first, optionally only those of one saved search
+    pub fn alerts(&self, search_id: Option<&str>) -> Vec<Alert> {
+        self.data
+            .read()
+            .unwrap()
+            .alerts
+            .iter()
+            .filter(|alert| search_id.is_none_or(|id| alert.search_id == id))
+            .cloned()
+            .collect()
+    }
+
+    pub fn unread_count(&self) -> usize {
+        self.data.read().unwrap().alerts.iter().filter(|alert| !alert.read).count()
+    }
+
+    pub fn mark_all_read(&self) {
+        for alert in self.data.write().unwrap().alerts.iter_mut() {
+            alert.read = true;
+        }
+        self.save();
+    }
+
+    // Run every saved search against newly stored articles and record what matched.
+    // Returns the new alerts grouped by the search that raised them.
+    pub fn evaluate(
+        &self,
+        new_ids: &[String],
+        index: &SearchIndex,
+        store: &ArticleStore,
+    ) -> Vec<(SavedSearch, Vec<Alert>)> {
+        let matched_at = Utc::now();
+        let raised: Vec<(SavedSearch, Vec<Alert>)> = self
+            .searches()
+            .into_iter()
+            .map(|search| {
+                let alerts: Vec<Alert> = index
+                    .matching(&search.query, new_ids)
+                    .into_iter()
+                    .filter_map(|id| store.get(&id))
+                    .map(|article| Alert {
+                        search_id: search.id.clone(),
+                        article_id: article.id,
+                        title: article.title,
+                        url: article.url,
+                        source: article.source,
+                        matched_at,
+                        read: false,
+                    })
+                    .collect();
+                (search, alerts)
+            })
+            .filter(|(_, alerts)| !alerts.is_empty())
+            .collect();
+
+        if !raised.is_empty() {
+            let mut data = self.data.write().unwrap();
+            let mut fresh: Vec<Alert> = raised.iter().flat_map(|(_, alerts)| alerts.iter().cloned()).collect();
+            fresh.append(&mut data.alerts);
+            fresh.truncate(self.max_alerts);
+            data.alerts = fresh;
+        }
+        raised
+    }
+
+    // Write searches and alerts to disk, replacing the previous file atomically
+    pub fn save(&self) {
+        self.file.save(&self.data.read().unwrap());
+    }
+}
+
+// Send a saved search's new alerts to each channel it asked for. Failures are logged;
+// the alerts stay in the inbox either way.
+pub async fn deliver(channels: &[ChannelConfig], search: &SavedSearch, alerts: &[Alert], public_url: &str) {
+    let client = match Client::builder().timeout(Duration::from_secs(10)).build() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to build alert delivery client: {}", e);
+            return;
+        }
+    };
+    let base = public_url.trim_end_matches('/');
+
+    for channel in channels.iter().filter(|channel| search.channels.contains(&channel.name)) {
+        let body = match channel.format {
+            ChannelFormat::Json => json!({
+                "search": { "id": search.id, "name": search.name, "query": search.query },
+                "alerts": alerts.iter().map(|alert| json!({
+                    "article_id": alert.article_id,
+                    "title": alert.title,
+                    "url": alert.url,
+                    "permalink": format!("{}{}", base, permalink(&alert.article_id)),
+                    "source": alert.source,
+                    "matched_at": alert.matched_at,
+                })).collect::<Vec<_>>(),
+            }),
+            ChannelFormat::Slack => {
+                let lines: Vec<String> = alerts
+                    .iter()
+                    .map(|alert| format!("• <{}|{}> ({})", alert.url, alert.title, alert.source))
+                    .collect();
+                json!({ "text": format!("New matches for \"{}\":\n{}", search.name, lines.join("\n")) })
+            }
+        };
+
+        match client.post(&channel.url).json(&body).send().await {
+            Ok(response) if !response.status().is_success() => {
+                eprintln!("Alert channel {} answered {}", channel.name, response.status());
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to deliver alerts to {}: {}", channel.name, e),
+        }
+    }
+}
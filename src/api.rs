@@ -0,0 +1,403 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rocket::http::{RawStr, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+use rocket::serde::json::Json;
+use rocket::{Catcher, Route, State};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::categorize::CategoryEngine;
+use crate::config::AppConfig;
+use crate::engagement::Engagement;
+use crate::ids::slugify;
+use crate::listing::{ListingParams, SortOrder};
+use crate::mute::Muting;
+use crate::pagination::{Paginated, Pagination};
+use crate::store::ArticleStore;
+use crate::taxonomy::Taxonomy;
+use crate::{BlogArticle, SOURCES};
+
+// Body of every error the API returns, e.g. {"status": 404, "error": "Not Found", "message": "..."}
+#[derive(Debug, Serialize)]
+pub struct ApiError {
+    pub status: u16,
+    pub error: String,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: Status, message: impl Into<String>) -> Self {
+        ApiError {
+            status: status.code,
+            error: status.reason_lossy().to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let status = Status::from_code(self.status).unwrap_or(Status::InternalServerError);
+        (status, Json(self)).respond_to(request)
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+// `GET /api/v1/articles` parameters: the listing ones plus category and tag filters
+#[derive(Debug, Clone, FromForm)]
+pub struct ArticleQuery {
+    #[field(default = SortOrder::Newest)]
+    sort: SortOrder,
+    source: Vec<String>,
+    category: Option<String>,
+    tag: Option<String>,
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+impl ArticleQuery {
+    fn listing(&self) -> ListingParams {
+        ListingParams {
+            sort: self.sort,
+            source: self.source.clone(),
+            page: self.page,
+            per_page: self.per_page,
+        }
+    }
+
+    // Base URL for page links, keeping every filter but the page itself
+    fn url(&self) -> String {
+        let mut query = self.listing().query_string();
+        for (name, value) in [("category", &self.category), ("tag", &self.tag)] {
+            if let Some(value) = value {
+                if !query.is_empty() {
+                    query.push('&');
+                }
+                query.push_str(&format!("{}={}", name, RawStr::new(value).percent_encode()));
+            }
+        }
+        if query.is_empty() {
+            "/api/v1/articles".to_string()
+        } else {
+            format!("/api/v1/articles?{}", query)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArticlePage {
+    pub articles: Vec<BlogArticle>,
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NamedCount {
+    pub name: String,
+    pub slug: String,
+    pub article_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagCount {
+    pub name: String,
+    pub parent: Option<String>,
+    pub article_count: usize,
+}
+
+#[get("/articles?<query..>")]
+async fn list_articles(
+    query: ArticleQuery,
+    store: &State<ArticleStore>,
+    taxonomy: &State<Taxonomy>,
+    config: &State<AppConfig>,
+    engagement: &State<Engagement>,
+    muting: Muting,
+) -> Paginated<Json<ArticlePage>> {
+    let category = query.category.as_deref().map(slugify);
+    let tag = query.tag.as_deref().map(|tag| taxonomy.canonical(tag));
+    let filtered: Vec<BlogArticle> = store
+        .all()
+        .into_iter()
+        .filter(|article| {
+            category.as_ref().is_none_or(|wanted| article.categories.iter().any(|c| slugify(c) == *wanted))
+                && tag.as_ref().is_none_or(|wanted| article.tags.iter().any(|t| taxonomy.is_within(t, wanted)))
+        })
+        .collect();
+
+    let filtered = muting.visible(filtered);
+    let listing = query.listing();
+    let articles = listing.apply(filtered, &config.ranking, engagement);
+    let pagination = Pagination::new(listing.paging(), articles.len(), &query.url());
+    Paginated {
+        inner: Json(ArticlePage {
+            articles: pagination.slice(&articles),
+            pagination: pagination.clone(),
+        }),
+        pagination,
+    }
+}
+
+// IDs contain a colon, e.g. /api/v1/articles/hacker-news:41234567
+#[get("/articles/<id>")]
+async fn get_article(id: &str, store: &State<ArticleStore>) -> ApiResult<Json<BlogArticle>> {
+    store
+        .get(id)
+        .map(Json)
+        .ok_or_else(|| ApiError::new(Status::NotFound, format!("No article with ID \"{}\"", id)))
+}
+
+#[get("/sources")]
+async fn list_sources(store: &State<ArticleStore>) -> Json<Vec<NamedCount>> {
+    // Scraped sources are listed even while they have no articles
+    let mut counts: BTreeMap<String, usize> = SOURCES.iter().map(|source| (source.to_string(), 0)).collect();
+    for article in store.all() {
+        for source in article.sources {
+            *counts.entry(source).or_default() += 1;
+        }
+    }
+    Json(named_counts(counts))
+}
+
+#[get("/categories")]
+async fn list_categories(store: &State<ArticleStore>, engine: &State<CategoryEngine>) -> Json<Vec<NamedCount>> {
+    let mut counts: BTreeMap<String, usize> = engine.known_categories().into_iter().map(|c| (c, 0)).collect();
+    for article in store.all() {
+        for category in article.categories {
+            *counts.entry(category).or_default() += 1;
+        }
+    }
+    Json(named_counts(counts))
+}
+
+#[get("/tags")]
+async fn list_tags(store: &State<ArticleStore>, taxonomy: &State<Taxonomy>) -> Json<Vec<TagCount>> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for article in store.all() {
+        for tag in article.tags {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+    let mut tags: Vec<TagCount> = counts
+        .into_iter()
+        .map(|(name, article_count)| TagCount {
+            parent: taxonomy.parent(&name).map(str::to_string),
+            name,
+            article_count,
+        })
+        .collect();
+    tags.sort_by(|a, b| b.article_count.cmp(&a.article_count).then_with(|| a.name.cmp(&b.name)));
+    Json(tags)
+}
+
+fn named_counts(counts: BTreeMap<String, usize>) -> Vec<NamedCount> {
+    counts
+        .into_iter()
+        .map(|(name, article_count)| NamedCount {
+            slug: slugify(&name),
+            name,
+            article_count,
+        })
+        .collect()
+}
+
+#[get("/openapi.json")]
+async fn openapi() -> Json<Value> {
+    Json(openapi_document())
+}
+
+#[catch(default)]
+fn api_error(status: Status, request: &Request<'_>) -> ApiError {
+    let message = match status.code {
+        404 => format!("No API resource at {}", request.uri().path()),
+        422 => "The request parameters are invalid".to_string(),
+        _ => status.reason_lossy().to_string(),
+    };
+    ApiError::new(status, message)
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![list_articles, get_article, list_sources, list_categories, list_tags, openapi]
+}
+
+pub fn catchers() -> Vec<Catcher> {
+    catchers![api_error]
+}
+
+// OpenAPI 3.0 description of the routes above
+fn openapi_document() -> Value {
+    let error = json!({ "$ref": "#/components/schemas/Error" });
+    let error_response = |description: &str| json!({
+        "description": description,
+        "content": { "application/json": { "schema": error } },
+    });
+    let json_response = |description: &str, schema: Value| json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema } },
+    });
+    let query_param = |name: &str, description: &str, schema: Value| json!({
+        "name": name, "in": "query", "required": false, "description": description, "schema": schema,
+    });
+    let named_counts = json!({ "type": "array", "items": { "$ref": "#/components/schemas/NamedCount" } });
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Blog Engine API",
+            "version": "1.0.0",
+            "description": "Aggregated tech news from every configured source.",
+        },
+        "servers": [{ "url": "/api/v1" }],
+        "paths": {
+            "/articles": {
+                "get": {
+                    "summary": "List articles",
+                    "parameters": [
+                        query_param("sort", "Sort order", json!({
+                            "type": "string",
+                            "enum": ["newest", "oldest", "popularity", "title", "ranking"],
+                            "default": "newest",
+                        })),
+                        query_param("source", "Only articles carried by this source; repeat for several", json!({
+                            "type": "array", "items": { "type": "string" },
+                        })),
+                        query_param("category", "Only articles in this category", json!({ "type": "string" })),
+                        query_param("tag", "Only articles with this tag or one below it", json!({ "type": "string" })),
+                        query_param("page", "Page number, starting at 1", json!({ "type": "integer", "minimum": 1, "default": 1 })),
+                        query_param("per_page", "Articles per page", json!({
+                            "type": "integer", "minimum": 1, "maximum": crate::pagination::MAX_PER_PAGE,
+                            "default": crate::pagination::DEFAULT_PER_PAGE,
+                        })),
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "One page of articles. A `Link` header points at the previous and next pages.",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/ArticlePage" } } },
+                        },
+                        "422": error_response("Invalid parameters, e.g. an unknown sort order"),
+                    },
+                },
+            },
+            "/articles/{id}": {
+                "get": {
+                    "summary": "Get one article",
+                    "parameters": [{
+                        "name": "id", "in": "path", "required": true,
+                        "description": "Article ID, e.g. hacker-news:41234567",
+                        "schema": { "type": "string" },
+                    }],
+                    "responses": {
+                        "200": json_response("The article", json!({ "$ref": "#/components/schemas/Article" })),
+                        "404": error_response("No article with that ID"),
+                    },
+                },
+            },
+            "/sources": {
+                "get": {
+                    "summary": "List sources with their article counts",
+                    "responses": { "200": json_response("Sources", named_counts.clone()) },
+                },
+            },
+            "/categories": {
+                "get": {
+                    "summary": "List categories with their article counts",
+                    "responses": { "200": json_response("Categories", named_counts) },
+                },
+            },
+            "/tags": {
+                "get": {
+                    "summary": "List tags in use, most used first",
+                    "responses": {
+                        "200": json_response("Tags", json!({ "type": "array", "items": { "$ref": "#/components/schemas/TagCount" } })),
+                    },
+                },
+            },
+        },
+        "components": {
+            "schemas": {
+                "Article": {
+                    "type": "object",
+                    "required": ["id", "title", "url", "excerpt", "tags", "content", "source", "sources", "fetched_at"],
+                    "properties": {
+                        "id": { "type": "string" },
+                        "title": { "type": "string" },
+                        "url": { "type": "string", "format": "uri" },
+                        "excerpt": { "type": "string" },
+                        "tags": { "type": "array", "items": { "type": "string" } },
+                        "keywords": { "type": "array", "items": { "type": "string" } },
+                        "content": { "type": "string" },
+                        "description": { "type": "string", "nullable": true },
+                        "category": { "type": "string", "nullable": true },
+                        "categories": { "type": "array", "items": { "type": "string" } },
+                        "manual_category": { "type": "string", "nullable": true },
+                        "feed_category": { "type": "string", "nullable": true },
+                        "source": { "type": "string" },
+                        "sources": { "type": "array", "items": { "type": "string" } },
+                        "published_at": { "type": "string", "format": "date-time", "nullable": true },
+                        "fetched_at": { "type": "string", "format": "date-time" },
+                        "enriched_at": { "type": "string", "format": "date-time", "nullable": true },
+                        "author": { "type": "string", "nullable": true },
+                        "image_url": { "type": "string", "nullable": true },
+                        "score": { "type": "integer", "nullable": true },
+                        "comment_count": { "type": "integer", "nullable": true },
+                        "language": { "type": "string", "nullable": true },
+                        "extra": { "type": "object", "additionalProperties": true },
+                    },
+                },
+                "ArticlePage": {
+                    "type": "object",
+                    "properties": {
+                        "articles": { "type": "array", "items": { "$ref": "#/components/schemas/Article" } },
+                        "pagination": { "$ref": "#/components/schemas/Pagination" },
+                    },
+                },
+                "Pagination": {
+                    "type": "object",
+                    "properties": {
+                        "current_page": { "type": "integer" },
+                        "per_page": { "type": "integer" },
+                        "total_items": { "type": "integer" },
+                        "total_pages": { "type": "integer" },
+                        "has_previous": { "type": "boolean" },
+                        "has_next": { "type": "boolean" },
+                        "previous_page_url": { "type": "string", "nullable": true },
+                        "next_page_url": { "type": "string", "nullable": true },
+                        "pages": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": { "number": { "type": "integer" }, "url": { "type": "string" } },
+                            },
+                        },
+                    },
+                },
+                "NamedCount": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "slug": { "type": "string" },
+                        "article_count": { "type": "integer" },
+                    },
+                },
+                "TagCount": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "parent": { "type": "string", "nullable": true },
+                        "article_count": { "type": "integer" },
+                    },
+                },
+                "Error": {
+                    "type": "object",
+                    "properties": {
+                        "status": { "type": "integer" },
+                        "error": { "type": "string" },
+                        "message": { "type": "string" },
+                    },
+                },
+            },
+        },
+    })
+}
@@ -0,0 +1,268 @@
+use regex::{Regex, RegexBuilder};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::classifier::Classifier;
+use crate::BlogArticle;
+
+// One categorization rule. Every condition list that is non-empty must match
+// (at least one entry per list); a rule with no conditions matches everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CategoryRule {
+    pub name: String,
+    // Higher priorities are evaluated first
+    pub priority: i32,
+    pub categories: Vec<String>,
+    pub sources: Vec<String>,
+    pub tags: Vec<String>,
+    // Matched against the URL host and its parent domains, ignoring "www."
+    pub hosts: Vec<String>,
+    // Regexes over the URL path
+    pub path_patterns: Vec<String>,
+    // Whole words or phrases in the title, excerpt, description or content, case-insensitive
+    pub keywords: Vec<String>,
+    // Regexes over the same text as keywords
+    pub patterns: Vec<String>,
+    // Skip lower-priority rules once this one matches
+    pub stop: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CategorizationConfig {
+    // Category given to articles no rule matches
+    pub fallback: String,
+    pub rules: Vec<CategoryRule>,
+}
+
+impl Default for CategorizationConfig {
+    fn default() -> Self {
+        CategorizationConfig {
+            fallback: "other".to_string(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: CategoryRule,
+    path_patterns: Vec<Regex>,
+    keywords: Option<Regex>,
+    patterns: Vec<Regex>,
+}
+
+// Result of evaluating the rules against one article
+#[derive(Debug, Serialize)]
+pub struct Categorization {
+    pub categories: Vec<String>,
+    pub matched_rules: Vec<String>,
+}
+
+// Categorizes articles with configured rules evaluated in priority order
+#[derive(Debug, Clone)]
+pub struct CategoryEngine {
+    rules: Vec<CompiledRule>,
+    fallback: String,
+}
+
+fn compile(rule: CategoryRule) -> Result<CompiledRule, String> {
+    let invalid = |e: regex::Error| format!("rule '{}': {}", rule.name, e);
+
+    let path_patterns = rule
+        .path_patterns
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(invalid))
+        .collect::<Result<Vec<_>, _>>()?;
+    let patterns = rule
+        .patterns
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(invalid))
+        .collect::<Result<Vec<_>, _>>()?;
+    let keywords = if rule.keywords.is_empty() {
+        None
+    } else {
+        let alternatives: Vec<String> = rule.keywords.iter().map(|k| regex::escape(k.trim())).collect();
+        let keywords = RegexBuilder::new(&format!(r"\b(?:{})\b", alternatives.join("|")))
+            .case_insensitive(true)
+            .build()
+            .map_err(invalid)?;
+        Some(keywords)
+    };
+
+    Ok(CompiledRule { rule, path_patterns, keywords, patterns })
+}
+
+pub fn host_matches(host: &str, wanted: &str) -> bool {
+    let wanted = wanted.trim().trim_start_matches("www.").to_lowercase();
+    host == wanted || host.ends_with(&format!(".{}", wanted))
+}
+
+impl CompiledRule {
+    fn matches(&self, article: &BlogArticle, text: &str) -> bool {
+        let rule = &self.rule;
+        let url = Url::parse(&article.url).ok();
+        let host = url
+            .as_ref()
+            .and_then(|url| url.host_str())
+            .map(|host| host.trim_start_matches("www.").to_lowercase())
+            .unwrap_or_default();
+        let path = url.as_ref().map(|url| url.path()).unwrap_or("");
+
+        (rule.sources.is_empty() || rule.sources.iter().any(|s| s.eq_ignore_ascii_case(&article.source)))
+            && (rule.tags.is_empty()
+                || rule.tags.iter().any(|wanted| article.tags.iter().any(|t| t.eq_ignore_ascii_case(wanted))))
+            && (rule.hosts.is_empty() || rule.hosts.iter().any(|wanted| host_matches(&host, wanted)))
+            && (self.path_patterns.is_empty() || self.path_patterns.iter().any(|p| p.is_match(path)))
+            && self.keywords.as_ref().is_none_or(|keywords| keywords.is_match(text))
+            && (self.patterns.is_empty() || self.patterns.iter().any(|p| p.is_match(text)))
+    }
+}
+
+impl CategoryEngine {
+    pub fn new(config: &CategorizationConfig) -> Result<Self, String> {
+        let mut rules = config
+            .rules
+            .iter()
+            .cloned()
+            .map(compile)
+            .collect::<Result<Vec<_>, _>>()?;
+        // Stable sort keeps file order among rules of equal priority
+        rules.sort_by_key(|compiled| std::cmp::Reverse(compiled.rule.priority));
+
+        Ok(CategoryEngine {
+            rules,
+            fallback: config.fallback.clone(),
+        })
+    }
+
+    pub fn evaluate(&self, article: &BlogArticle) -> Categorization {
+        let text = format!(
+            "{}\n{}\n{}\n{}",
+            article.title,
+            article.excerpt,
+            article.description.as_deref().unwrap_or(""),
+            article.content
+        );
+
+        let mut result = Categorization { categories: Vec::new(), matched_rules: Vec::new() };
+        for compiled in &self.rules {
+            if !compiled.matches(article, &text) {
+                continue;
+            }
+            result.matched_rules.push(compiled.rule.name.clone());
+            for category in &compiled.rule.categories {
+                let category = category.to_lowercase();
+                if !result.categories.contains(&category) {
+                    result.categories.push(category);
+                }
+            }
+            if compiled.rule.stop {
+                break;
+            }
+        }
+
+        if result.categories.is_empty() {
+            result.categories.push(self.fallback.clone());
+        }
+        result
+    }
+
+    // Set the article's categories; the first one is its primary category
+    pub fn apply(&self, article: &mut BlogArticle) {
+        let categorization = self.evaluate(article);
+        article.category = categorization.categories.first().cloned();
+        article.categories = categorization.categories;
+    }
+
+    // Every category some rule can assign, plus the fallback
+    pub fn known_categories(&self) -> Vec<String> {
+        let mut categories: Vec<String> = self
+            .rules
+            .iter()
+            .flat_map(|compiled| compiled.rule.categories.iter().map(|c| c.to_lowercase()))
+            .collect();
+        categories.push(self.fallback.clone());
+        categories.sort();
+        categories.dedup();
+        categories
+    }
+}
+
+// Categorize an article using, in order: a manual choice, the folder of its feed
+// source, a confident classifier prediction, and finally the rule engine
+pub fn categorize(engine: &CategoryEngine, classifier: &Classifier, article: &mut BlogArticle) {
+    let chosen = article
+        .manual_category
+        .clone()
+        .or_else(|| article.feed_category.clone())
+        .or_else(|| classifier.predict(article));
+    match chosen {
+        Some(category) => {
+            article.category = Some(category.clone());
+            article.categories = vec![category];
+        }
+        None => engine.apply(article),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_article;
+
+    fn rule(name: &str, priority: i32, category: &str, keywords: &[&str], stop: bool) -> CategoryRule {
+        CategoryRule {
+            name: name.to_string(),
+            priority,
+            categories: vec![category.to_string()],
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            stop,
+            ..CategoryRule::default()
+        }
+    }
+
+    fn engine(rules: Vec<CategoryRule>) -> CategoryEngine {
+        CategoryEngine::new(&CategorizationConfig { fallback: "other".to_string(), rules }).unwrap()
+    }
+
+    #[test]
+    fn rules_run_by_priority_until_one_stops() {
+        let article = test_article("hacker-news:1", "Rust exploit found in the compiler");
+        let rules = vec![
+            rule("rust", 1, "Tech", &["rust"], false),
+            rule("exploit", 5, "Security", &["exploit"], false),
+            rule("compiler", 3, "tech", &["compiler"], false),
+        ];
+
+        let result = engine(rules.clone()).evaluate(&article);
+        assert_eq!(result.matched_rules, ["exploit", "compiler", "rust"]);
+        assert_eq!(result.categories, ["security", "tech"]);
+
+        let mut stopping = rules;
+        stopping[1].stop = true;
+        let result = engine(stopping).evaluate(&article);
+        assert_eq!(result.matched_rules, ["exploit"]);
+        assert_eq!(result.categories, ["security"]);
+    }
+
+    #[test]
+    fn unmatched_articles_get_the_fallback() {
+        let engine = engine(vec![rule("rust", 1, "tech", &["rust"], false)]);
+        // Keywords match whole words only
+        let mut article = test_article("hacker-news:1", "Trusty old bikes");
+        engine.apply(&mut article);
+        assert_eq!(article.category.as_deref(), Some("other"));
+        assert_eq!(article.categories, ["other"]);
+        assert_eq!(engine.known_categories(), ["other", "tech"]);
+    }
+
+    #[test]
+    fn invalid_patterns_are_reported_with_the_rule_name() {
+        let broken = CategoryRule { name: "broken".to_string(), patterns: vec!["(".to_string()], ..CategoryRule::default() };
+        let error = CategoryEngine::new(&CategorizationConfig { rules: vec![broken], ..CategorizationConfig::default() })
+            .unwrap_err();
+        assert!(error.starts_with("rule 'broken'"));
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::json_file::JsonFile;
+use crate::text::tokenize;
+use crate::BlogArticle;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClassifierConfig {
+    // Use predictions when categorizing; training happens either way
+    pub enabled: bool,
+    // Labelled JSONL used to train the model when no saved model exists
+    pub training_file: Option<PathBuf>,
+    // Minimum posterior probability for a prediction to win over the rule engine
+    pub threshold: f64,
+    // Do not predict until the model has seen this many documents
+    pub min_documents: u64,
+}
+
+impl Default for ClassifierConfig {
+    fn default() -> Self {
+        ClassifierConfig {
+            enabled: false,
+            training_file: None,
+            threshold: 0.8,
+            min_documents: 20,
+        }
+    }
+}
+
+// One line of the training file; only category is required
+#[derive(Debug, Deserialize)]
+struct LabelledExample {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    excerpt: String,
+    #[serde(default)]
+    content: String,
+    category: String,
+}
+
+// Multinomial naive Bayes with Laplace smoothing
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct NaiveBayes {
+    documents: u64,
+    class_documents: HashMap<String, u64>,
+    class_terms: HashMap<String, u64>,
+    term_counts: HashMap<String, HashMap<String, u64>>,
+    vocabulary: HashSet<String>,
+}
+
+impl NaiveBayes {
+    fn train(&mut self, category: &str, tokens: &[String]) {
+        self.documents += 1;
+        *self.class_documents.entry(category.to_string()).or_default() += 1;
+        *self.class_terms.entry(category.to_string()).or_default() += tokens.len() as u64;
+
+        let counts = self.term_counts.entry(category.to_string()).or_default();
+        for token in tokens {
+            *counts.entry(token.clone()).or_default() += 1;
+            self.vocabulary.insert(token.clone());
+        }
+    }
+
+    // Most likely category and its posterior probability
+    fn predict(&self, tokens: &[String]) -> Option<(String, f64)> {
+        let vocabulary = self.vocabulary.len() as f64;
+        let scores: Vec<(String, f64)> = self
+            .class_documents
+            .iter()
+            .map(|(category, &documents)| {
+                let prior = (documents as f64 / self.documents as f64).ln();
+                let counts = &self.term_counts[category];
+                let total = self.class_terms[category] as f64;
+                let likelihood: f64 = tokens
+                    .iter()
+                    .map(|token| {
+                        let count = counts.get(token).copied().unwrap_or(0) as f64;
+                        ((count + 1.0) / (total + vocabulary)).ln()
+                    })
+                    .sum();
+                (category.clone(), prior + likelihood)
+            })
+            .collect();
+
+        // Normalize log scores into probabilities without underflowing
+        let best = scores.iter().map(|(_, score)| *score).fold(f64::NEG_INFINITY, f64::max);
+        let normalizer: f64 = scores.iter().map(|(_, score)| (score - best).exp()).sum();
+        scores
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(category, score)| (category, (score - best).exp() / normalizer))
+    }
+}
+
+fn article_tokens(title: &str, excerpt: &str, content: &str) -> Vec<String> {
+    // Titles are short but the most telling part, so they count twice
+    let mut tokens = tokenize(title);
+    tokens.extend(tokenize(title));
+    tokens.extend(tokenize(excerpt));
+    tokens.extend(tokenize(content));
+    tokens
+}
+
+// Category classifier shared by the refresh pipeline and the re-categorization route.
+// Cloning is cheap: clones share the same model.
+#[derive(Clone)]
+pub struct Classifier {
+    model: Arc<RwLock<NaiveBayes>>,
+    config: ClassifierConfig,
+    file: JsonFile<NaiveBayes>,
+}
+
+fn train_from_file(model: &mut NaiveBayes, path: &Path) {
+    let jsonl = match fs::read_to_string(path) {
+        Ok(jsonl) => jsonl,
+        Err(e) => {
+            eprintln!("Failed to read training file {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    for (number, line) in jsonl.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        match serde_json::from_str::<LabelledExample>(line) {
+            Ok(example) => {
+                let tokens = article_tokens(&example.title, &example.excerpt, &example.content);
+                model.train(&example.category.to_lowercase(), &tokens);
+            }
+            Err(e) => eprintln!("Skipping line {} of {}: {}", number + 1, path.display(), e),
+        }
+    }
+}
+
+impl Classifier {
+    // Load the saved model, or train a new one from the configured training file
+    pub fn open(path: PathBuf, config: ClassifierConfig) -> Self {
+        let file = JsonFile::new(path, "classifier");
+        let classifier = Classifier {
+            model: Arc::new(RwLock::new(file.load().unwrap_or_default())),
+            config,
+            file,
+        };
+
+        if classifier.model.read().unwrap().documents == 0 {
+            if let Some(training_file) = &classifier.config.training_file {
+                train_from_file(&mut classifier.model.write().unwrap(), training_file);
+                classifier.save();
+            }
+        }
+        classifier
+    }
+
+    // Predicted category when the model is enabled, trained enough and confident
+    pub fn predict(&self, article: &BlogArticle) -> Option<String> {
+        let model = self.model.read().unwrap();
+        if !self.config.enabled || model.documents < self.config.min_documents {
+            return None;
+        }
+
+        let tokens = article_tokens(&article.title, &article.excerpt, &article.content);
+        model
+            .predict(&tokens)
+            .filter(|(_, probability)| *probability >= self.config.threshold)
+            .map(|(category, _)| category)
+    }
+
+    // Retrain on the training file plus every article's current manual category and persist
+    // the model. Relabelling an article replaces its example rather than adding another.
+    pub fn rebuild(&self, articles: &[BlogArticle]) {
+        let mut model = NaiveBayes::default();
+        if let Some(training_file) = &self.config.training_file {
+            train_from_file(&mut model, training_file);
+        }
+        for article in articles {
+            if let Some(category) = &article.manual_category {
+                model.train(category, &article_tokens(&article.title, &article.excerpt, &article.content));
+            }
+        }
+        *self.model.write().unwrap() = model;
+        self.save();
+    }
+
+    // Replaces the previous file atomically, so a crash mid-write cannot lose the model
+    fn save(&self) {
+        self.file.save(&self.model.read().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_article;
+
+    fn tokens(text: &str) -> Vec<String> {
+        article_tokens(text, "", "")
+    }
+
+    #[test]
+    fn predicts_the_category_with_matching_words() {
+        let mut model = NaiveBayes::default();
+        model.train("security", &tokens("ransomware breach exposes passwords"));
+        model.train("security", &tokens("zero-day exploit patched in browser"));
+        model.train("tech", &tokens("rust compiler release brings faster builds"));
+        model.train("tech", &tokens("new database engine for developers"));
+
+        let (category, probability) = model.predict(&tokens("browser exploit leaks passwords")).unwrap();
+        assert_eq!(category, "security");
+        assert!(probability > 0.5);
+        assert_eq!(model.predict(&tokens("faster rust builds")).unwrap().0, "tech");
+    }
+
+    #[test]
+    fn relabelling_replaces_the_previous_example() {
+        let config = ClassifierConfig { enabled: true, min_documents: 1, threshold: 0.0, ..ClassifierConfig::default() };
+        let classifier = Classifier::open(PathBuf::from("/nonexistent/classifier.json"), config);
+        let mut article = test_article("medium:1", "Ransomware hits hospitals");
+
+        for category in ["news", "security", "security"] {
+            article.manual_category = Some(category.to_string());
+            classifier.rebuild(std::slice::from_ref(&article));
+        }
+
+        let model = classifier.model.read().unwrap();
+        assert_eq!(model.documents, 1);
+        assert_eq!(model.class_documents.get("security"), Some(&1));
+        assert!(!model.class_documents.contains_key("news"));
+    }
+}
@@ -0,0 +1,157 @@
+use serde::Serialize;
+
+use crate::text::tokenize;
+use crate::{ids, BlogArticle};
+
+// Number of hash functions in each MinHash signature
+const SIGNATURE_SIZE: usize = 64;
+
+// Estimated Jaccard similarity above which two articles cover the same story
+const SIMILARITY_THRESHOLD: f64 = 0.4;
+
+// Top-ranked articles grouped into stories at most; every pair of them is compared
+pub const MAX_ARTICLES: usize = 300;
+
+// One story as seen across sources: the best article plus the ones that repeat it
+#[derive(Debug, Serialize, Clone)]
+pub struct StoryCluster {
+    pub representative: BlogArticle,
+    pub related: Vec<BlogArticle>,
+}
+
+// SplitMix64 finalizer, used to derive independent hash functions from one seed
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
+
+// Word unigrams and bigrams over the title and excerpt
+fn shingles(article: &BlogArticle) -> Vec<String> {
+    let mut words = tokenize(&article.title);
+    words.extend(tokenize(&article.excerpt));
+
+    let mut shingles: Vec<String> = words.windows(2).map(|pair| pair.join(" ")).collect();
+    shingles.extend(words);
+    shingles.sort();
+    shingles.dedup();
+    shingles
+}
+
+fn signature(article: &BlogArticle) -> Option<Vec<u64>> {
+    let hashes: Vec<u64> = shingles(article).iter().map(|s| ids::stable_hash(s)).collect();
+    if hashes.is_empty() {
+        return None;
+    }
+
+    let signature = (0..SIGNATURE_SIZE as u64)
+        .map(|seed| {
+            let salt = mix(seed.wrapping_add(1));
+            hashes.iter().map(|&hash| mix(hash ^ salt)).min().unwrap_or(u64::MAX)
+        })
+        .collect();
+    Some(signature)
+}
+
+fn estimated_similarity(a: &[u64], b: &[u64]) -> f64 {
+    let matching = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    matching as f64 / SIGNATURE_SIZE as f64
+}
+
+fn find(parents: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    parents[index] = root;
+    root
+}
+
+// Prefer the article seen on the most sources, then the one with the most content
+fn representative_index(articles: &[BlogArticle]) -> usize {
+    articles
+        .iter()
+        .enumerate()
+        .max_by(|(ia, a), (ib, b)| {
+            a.sources
+                .len()
+                .cmp(&b.sources.len())
+                .then(a.content.len().cmp(&b.content.len()))
+                // Earlier articles win ties so the order stays stable
+                .then(ib.cmp(ia))
+        })
+        .map(|(index, _)| index)
+        .unwrap_or(0)
+}
+
+// Group near-duplicate articles into stories, keeping the order in which stories first appear
+pub fn cluster_articles(articles: Vec<BlogArticle>) -> Vec<StoryCluster> {
+    let signatures: Vec<Option<Vec<u64>>> = articles.iter().map(signature).collect();
+    let mut parents: Vec<usize> = (0..articles.len()).collect();
+
+    for i in 0..articles.len() {
+        for j in (i + 1)..articles.len() {
+            if let (Some(a), Some(b)) = (&signatures[i], &signatures[j]) {
+                if estimated_similarity(a, b) >= SIMILARITY_THRESHOLD {
+                    let (root_i, root_j) = (find(&mut parents, i), find(&mut parents, j));
+                    if root_i != root_j {
+                        parents[root_j.max(root_i)] = root_i.min(root_j);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<(usize, Vec<BlogArticle>)> = Vec::new();
+    for (index, article) in articles.into_iter().enumerate() {
+        let root = find(&mut parents, index);
+        match groups.iter_mut().find(|(group_root, _)| *group_root == root) {
+            Some((_, members)) => members.push(article),
+            None => groups.push((root, vec![article])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(_, mut members)| {
+            let representative = members.remove(representative_index(&members));
+            StoryCluster { representative, related: members }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_article;
+
+    fn similarity(a: &BlogArticle, b: &BlogArticle) -> f64 {
+        estimated_similarity(&signature(a).unwrap(), &signature(b).unwrap())
+    }
+
+    #[test]
+    fn near_duplicates_cross_the_threshold_and_unrelated_stories_do_not() {
+        let original = test_article("hacker-news:1", "Rust 2.0 released with async closures and faster compile times");
+        let retold = test_article("reddit:1", "Rust 2.0 released with async closures and much faster compile times");
+        let unrelated = test_article("lobsters:1", "Postgres adds incremental materialized view refresh");
+
+        assert_eq!(similarity(&original, &original), 1.0);
+        assert!(similarity(&original, &retold) >= SIMILARITY_THRESHOLD);
+        assert!(similarity(&original, &unrelated) < SIMILARITY_THRESHOLD);
+    }
+
+    #[test]
+    fn clusters_keep_first_seen_order_and_pick_the_widest_article() {
+        let original = test_article("hacker-news:1", "Rust 2.0 released with async closures and faster compile times");
+        let unrelated = test_article("lobsters:1", "Postgres adds incremental materialized view refresh");
+        let mut retold = test_article("reddit:1", "Rust 2.0 released with async closures and much faster compile times");
+        retold.sources = vec!["reddit".to_string(), "medium".to_string()];
+        let empty = test_article("medium:1", "");
+
+        let clusters = cluster_articles(vec![original, unrelated, retold, empty]);
+        let shape: Vec<(&str, usize)> =
+            clusters.iter().map(|story| (story.representative.id.as_str(), story.related.len())).collect();
+        assert_eq!(shape, [("reddit:1", 1), ("lobsters:1", 0), ("medium:1", 0)]);
+        assert_eq!(clusters[0].related[0].id, "hacker-news:1");
+    }
+}
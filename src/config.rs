@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::alerts::AlertsConfig;
+use crate::categorize::CategorizationConfig;
+use crate::classifier::ClassifierConfig;
+use crate::keywords::KeywordConfig;
+use crate::mute::MuteRules;
+use crate::ranking::RankingConfig;
+use crate::sources::SourcesConfig;
+use crate::taxonomy::TaxonomyConfig;
+use crate::trending::TrendingConfig;
+
+// Application settings read from Rocket.toml alongside Rocket's own keys
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    // Directory holding the article store and other persisted state
+    pub data_dir: PathBuf,
+    // Seconds between two runs of the refresh pipeline
+    pub refresh_interval: u64,
+    // Days an article is kept after it was first fetched
+    pub retention_days: u64,
+    // Where readers reach the site, for absolute links in feeds and notifications
+    pub public_url: String,
+    // Unlocks routes that change shared state, like saved searches; they are disabled without one
+    pub admin_token: Option<String>,
+    pub categorization: CategorizationConfig,
+    pub classifier: ClassifierConfig,
+    pub taxonomy: TaxonomyConfig,
+    pub keywords: KeywordConfig,
+    pub trending: TrendingConfig,
+    pub ranking: RankingConfig,
+    // Hidden from every reader
+    pub mute: MuteRules,
+    pub alerts: AlertsConfig,
+    pub sources: SourcesConfig,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            data_dir: PathBuf::from("data"),
+            refresh_interval: 900,
+            retention_days: 30,
+            public_url: "http://127.0.0.1:8080".to_string(),
+            admin_token: None,
+            categorization: CategorizationConfig::default(),
+            classifier: ClassifierConfig::default(),
+            taxonomy: TaxonomyConfig::default(),
+            keywords: KeywordConfig::default(),
+            trending: TrendingConfig::default(),
+            ranking: RankingConfig::default(),
+            mute: MuteRules::default(),
+            alerts: AlertsConfig::default(),
+            sources: SourcesConfig::default(),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use reqwest::Url;
+
+use crate::BlogArticle;
+
+// Query parameters that only track where a click came from and never change the page
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid", "ref", "ref_src", "source", "guccounter", "mc_cid", "mc_eid"];
+
+// Placeholders the scrapers fall back to when upstream has nothing to offer
+const PLACEHOLDERS: &[&str] = &["No title", "No excerpt", "No description", "No content"];
+
+// Normalize a URL so the same story linked from different sources compares equal.
+// Scheme, "www." prefix, fragment, trailing slash and tracking parameters are ignored.
+pub fn canonical_url(url: &str) -> Option<String> {
+    let parsed = Url::parse(url.trim()).ok()?;
+    let host = parsed.host_str()?.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+
+    let mut query: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_ref()))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    query.sort();
+
+    let mut canonical = format!("{}{}", host, parsed.path().trim_end_matches('/'));
+    if !query.is_empty() {
+        let pairs: Vec<String> = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        canonical.push('?');
+        canonical.push_str(&pairs.join("&"));
+    }
+    Some(canonical)
+}
+
+// Empty, or one of the stand-ins scrapers use when a source has no text
+pub fn is_placeholder(text: &str) -> bool {
+    let text = text.trim();
+    text.is_empty() || PLACEHOLDERS.contains(&text)
+}
+
+// Keep whichever of the two texts carries more information
+fn richer(current: &mut String, candidate: &str) {
+    if is_placeholder(current) || (!is_placeholder(candidate) && candidate.len() > current.len()) {
+        *current = candidate.to_string();
+    }
+}
+
+fn push_unique(values: &mut Vec<String>, extra: &[String]) {
+    for value in extra {
+        if !values.contains(value) {
+            values.push(value.clone());
+        }
+    }
+}
+
+// Fold a duplicate into the article that was seen first
+pub fn merge_into(primary: &mut BlogArticle, duplicate: BlogArticle) {
+    if is_placeholder(&primary.title) {
+        primary.title = duplicate.title;
+    }
+    richer(&mut primary.excerpt, &duplicate.excerpt);
+    richer(&mut primary.content, &duplicate.content);
+    if primary.description.as_deref().is_none_or(is_placeholder) {
+        primary.description = duplicate.description;
+    }
+    if primary.category.is_none() {
+        primary.category = duplicate.category;
+    }
+    push_unique(&mut primary.tags, &duplicate.tags);
+    push_unique(&mut primary.sources, &duplicate.sources);
+
+    // The earliest sighting is the closest to the real publication time
+    primary.published_at = match (primary.published_at, duplicate.published_at) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    primary.enriched_at = primary.enriched_at.or(duplicate.enriched_at);
+    primary.author = primary.author.take().or(duplicate.author);
+    primary.image_url = primary.image_url.take().or(duplicate.image_url);
+    primary.score = primary.score.max(duplicate.score);
+    primary.comment_count = primary.comment_count.max(duplicate.comment_count);
+    primary.language = primary.language.take().or(duplicate.language);
+    for (key, value) in duplicate.extra {
+        primary.extra.entry(key).or_insert(value);
+    }
+}
+
+// Merge articles that point at the same canonical URL, preserving first-seen order.
+// Articles without a usable URL (e.g. HN "Ask" posts) are never merged.
+pub fn dedup_articles(articles: Vec<BlogArticle>) -> Vec<BlogArticle> {
+    let mut merged: Vec<BlogArticle> = Vec::with_capacity(articles.len());
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for mut article in articles {
+        if article.sources.is_empty() {
+            article.sources.push(article.source.clone());
+        }
+
+        match canonical_url(&article.url) {
+            Some(key) => match seen.get(&key) {
+                Some(&index) => merge_into(&mut merged[index], article),
+                None => {
+                    seen.insert(key, merged.len());
+                    merged.push(article);
+                }
+            },
+            None => merged.push(article),
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_article;
+
+    #[test]
+    fn canonical_url_ignores_presentation_and_tracking() {
+        let canonical = Some("example.com/post?a=1&b=2".to_string());
+        assert_eq!(canonical_url("https://www.Example.com/post/?b=2&a=1#comments"), canonical);
+        assert_eq!(canonical_url("http://example.com/post?a=1&utm_source=x&b=2&fbclid=y"), canonical);
+        assert_ne!(canonical_url("https://example.com/post?a=2&b=2"), canonical);
+        assert_eq!(canonical_url("item?id=1"), None);
+    }
+
+    #[test]
+    fn duplicates_merge_into_the_first_sighting() {
+        let mut first = test_article("hacker-news:1", "Rust 2.0 released");
+        first.url = "https://blog.rust-lang.org/2.0/".to_string();
+        first.score = Some(120);
+        let mut second = test_article("reddit:9", "No title");
+        second.url = "https://blog.rust-lang.org/2.0?utm_source=reddit".to_string();
+        second.content = "The full announcement, longer than the title".to_string();
+        second.score = Some(300);
+        second.tags = vec!["rust".to_string()];
+        let mut ask = test_article("hacker-news:2", "Ask HN: Rust 2.0?");
+        ask.url = String::new();
+        let mut other_ask = test_article("hacker-news:3", "Ask HN: Rust 2.0?");
+        other_ask.url = String::new();
+
+        let merged = dedup_articles(vec![first, ask, second, other_ask]);
+        let ids: Vec<&str> = merged.iter().map(|article| article.id.as_str()).collect();
+        assert_eq!(ids, ["hacker-news:1", "hacker-news:2", "hacker-news:3"]);
+
+        let story = &merged[0];
+        assert_eq!(story.title, "Rust 2.0 released");
+        assert_eq!(story.content, "The full announcement, longer than the title");
+        assert_eq!(story.sources, ["hacker-news", "reddit"]);
+        assert_eq!(story.tags, ["rust"]);
+        assert_eq!(story.score, Some(300));
+    }
+}
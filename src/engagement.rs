@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use crate::json_file::JsonFile;
+
+// Local page views per article ID, persisted as JSON next to the article store.
+// Cloning is cheap: clones share the same counts.
+#[derive(Clone)]
+pub struct Engagement {
+    views: Arc<RwLock<HashMap<String, u64>>>,
+    file: JsonFile<HashMap<String, u64>>,
+}
+
+impl Engagement {
+    // Load the counts from disk, starting empty if the file is missing or unreadable
+    pub fn open(path: PathBuf) -> Self {
+        let file = JsonFile::new(path, "engagement counts");
+        let views = file.load().unwrap_or_default();
+
+        Engagement {
+            views: Arc::new(RwLock::new(views)),
+            file,
+        }
+    }
+
+    pub fn record_view(&self, id: &str) {
+        *self.views.write().unwrap().entry(id.to_string()).or_default() += 1;
+    }
+
+    // Drop the counts of articles that left the store
+    pub fn forget(&self, ids: &[String]) {
+        let mut views = self.views.write().unwrap();
+        for id in ids {
+            views.remove(id);
+        }
+    }
+
+    pub fn views(&self, id: &str) -> u64 {
+        self.views.read().unwrap().get(id).copied().unwrap_or(0)
+    }
+
+    // Write the counts to disk, replacing the previous file atomically.
+    // Called from the refresh pipeline, so at most one interval of views is lost on a crash.
+    pub fn save(&self) {
+        self.file.save(&self.views.read().unwrap());
+    }
+}
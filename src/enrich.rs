@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use reqwest::Client;
+use scraper::{Html, Selector};
+use serde_json::Value;
+
+use crate::store::ArticleStore;
+use crate::{dedup, parse_timestamp, BlogArticle};
+
+// Stop reading a page once this much has arrived, whether or not </head> was seen
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+// How many article pages are fetched at the same time
+const CONCURRENT_FETCHES: usize = 8;
+
+// Pages remembered at most, and for how long; the oldest are dropped first
+const CACHE_CAPACITY: usize = 5000;
+const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// JSON-LD @type values that describe an article
+const ARTICLE_TYPES: &[&str] = &["Article", "NewsArticle", "BlogPosting", "TechArticle", "Report"];
+
+// Metadata read from an article page's <head>
+#[derive(Debug, Clone, Default)]
+pub struct PageMeta {
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub author: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+// Results per canonical URL with the time they were read, so a page is not fetched again on
+// every refresh. Failed fetches are left out and retried next time.
+fn cache() -> &'static Mutex<HashMap<String, (Instant, PageMeta)>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, (Instant, PageMeta)>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn cached(key: &str) -> Option<PageMeta> {
+    let cache = cache().lock().unwrap();
+    cache
+        .get(key)
+        .filter(|(read_at, _)| read_at.elapsed() < CACHE_TTL)
+        .map(|(_, meta)| meta.clone())
+}
+
+// Remember a page, dropping expired entries and then the oldest to stay within CACHE_CAPACITY
+fn remember(key: String, meta: PageMeta) {
+    let mut cache = cache().lock().unwrap();
+    if cache.len() >= CACHE_CAPACITY {
+        cache.retain(|_, (read_at, _)| read_at.elapsed() < CACHE_TTL);
+    }
+    while cache.len() >= CACHE_CAPACITY {
+        let Some(oldest) = cache.iter().min_by_key(|(_, (read_at, _))| *read_at).map(|(key, _)| key.clone()) else {
+            break;
+        };
+        cache.remove(&oldest);
+    }
+    cache.insert(key, (Instant::now(), meta));
+}
+
+fn needs_enrichment(article: &BlogArticle) -> bool {
+    article.url.starts_with("http")
+        && (article.description.as_deref().is_none_or(str::is_empty)
+            || article.image_url.is_none()
+            || article.author.is_none()
+            || article.published_at.is_none())
+}
+
+// Download the start of a page, stopping after </head> or MAX_HEAD_BYTES
+async fn fetch_head(client: &Client, url: &str) -> Option<String> {
+    let mut response = match client.get(url).header("User-Agent", "Mozilla/5.0").send().await {
+        Ok(response) if response.status().is_success() => response,
+        Ok(response) => {
+            eprintln!("Failed to enrich {}: HTTP {}", url, response.status());
+            return None;
+        }
+        Err(e) => {
+            eprintln!("Failed to enrich {}: {}", url, e);
+            return None;
+        }
+    };
+
+    let mut body: Vec<u8> = Vec::new();
+    while let Ok(Some(chunk)) = response.chunk().await {
+        body.extend_from_slice(&chunk);
+        if body.len() >= MAX_HEAD_BYTES || String::from_utf8_lossy(&body).contains("</head>") {
+            break;
+        }
+    }
+    body.truncate(MAX_HEAD_BYTES);
+
+    Some(String::from_utf8_lossy(&body).into_owned())
+}
+
+// JSON-LD values can be a string, an object with a name/url, or a list of either
+fn json_ld_text(value: &Value, key: &str) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Object(object) => object.get(key).and_then(|inner| json_ld_text(inner, key)),
+        Value::Array(items) => items.iter().find_map(|item| json_ld_text(item, key)),
+        _ => None,
+    }
+}
+
+fn is_article(node: &Value) -> bool {
+    match &node["@type"] {
+        Value::String(kind) => ARTICLE_TYPES.contains(&kind.as_str()),
+        Value::Array(kinds) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|kind| ARTICLE_TYPES.contains(&kind)),
+        _ => false,
+    }
+}
+
+// Find the first Article node in a JSON-LD document, looking inside lists and @graph
+fn find_article(node: &Value) -> Option<&Value> {
+    if is_article(node) {
+        return Some(node);
+    }
+    match node {
+        Value::Array(items) => items.iter().find_map(find_article),
+        Value::Object(object) => object.get("@graph").and_then(find_article),
+        _ => None,
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+// Read OpenGraph, Twitter card, JSON-LD and standard meta tags
+pub fn parse_head(html: &str) -> PageMeta {
+    let document = Html::parse_document(html);
+    let meta_selector = Selector::parse("meta").unwrap();
+    let json_ld_selector = Selector::parse(r#"script[type="application/ld+json"]"#).unwrap();
+
+    let mut tags: HashMap<String, String> = HashMap::new();
+    for element in document.select(&meta_selector) {
+        let attrs = element.value();
+        let key = attrs.attr("property").or_else(|| attrs.attr("name")).or_else(|| attrs.attr("itemprop"));
+        if let (Some(key), Some(content)) = (key, attrs.attr("content")) {
+            tags.entry(key.to_lowercase()).or_insert_with(|| content.to_string());
+        }
+    }
+    let tag = |keys: &[&str]| keys.iter().find_map(|key| non_empty(tags.get(*key).map(String::as_str)));
+
+    let json_ld: Option<Value> = document
+        .select(&json_ld_selector)
+        .filter_map(|script| serde_json::from_str::<Value>(&script.inner_html()).ok())
+        .find_map(|node| find_article(&node).cloned());
+    let ld = |key: &str, field: &str| {
+        json_ld
+            .as_ref()
+            .and_then(|node| json_ld_text(&node[key], field))
+            .and_then(|text| non_empty(Some(&text)))
+    };
+
+    // article:author is frequently a profile URL rather than a name
+    let meta_author = tag(&["author", "article:author", "twitter:creator"])
+        .filter(|author| !author.starts_with("http"));
+
+    PageMeta {
+        description: tag(&["og:description", "twitter:description", "description"])
+            .or_else(|| ld("description", "description")),
+        image_url: tag(&["og:image", "og:image:url", "twitter:image", "twitter:image:src"])
+            .or_else(|| ld("image", "url")),
+        author: ld("author", "name").or(meta_author),
+        published_at: tag(&["article:published_time", "datepublished", "date", "pubdate"])
+            .or_else(|| ld("datePublished", "datePublished"))
+            .and_then(|value| parse_timestamp(&value)),
+    }
+}
+
+// Metadata of a page, or None if it could not be fetched
+async fn page_meta(client: &Client, url: &str) -> Option<PageMeta> {
+    let key = dedup::canonical_url(url).unwrap_or_else(|| url.to_string());
+    if let Some(meta) = cached(&key) {
+        return Some(meta);
+    }
+
+    let meta = parse_head(&fetch_head(client, url).await?);
+    remember(key, meta.clone());
+    Some(meta)
+}
+
+// Fill fields the source left empty; never overwrite what upstream provided
+fn apply(article: &mut BlogArticle, meta: PageMeta) {
+    article.enriched_at = Some(Utc::now());
+    if article.description.as_deref().is_none_or(str::is_empty) {
+        article.description = meta.description;
+    }
+    if dedup::is_placeholder(&article.excerpt) {
+        if let Some(description) = &article.description {
+            article.excerpt = description.clone();
+        }
+    }
+    article.image_url = article.image_url.take().or(meta.image_url);
+    article.author = article.author.take().or(meta.author);
+    article.published_at = article.published_at.or(meta.published_at);
+}
+
+// Enrich articles that are missing metadata from their own page's <head>. Each page is read
+// once: articles already stored with page metadata keep it when they are merged back in.
+pub async fn enrich_articles(articles: Vec<BlogArticle>, store: &ArticleStore) -> Vec<BlogArticle> {
+    let client = Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("Failed to create client");
+
+    stream::iter(articles)
+        .map(|mut article| {
+            let client = &client;
+            async move {
+                let enriched = store.get(&article.id).is_some_and(|stored| stored.enriched_at.is_some());
+                if !enriched && needs_enrichment(&article) {
+                    if let Some(meta) = page_meta(client, &article.url).await {
+                        apply(&mut article, meta);
+                    }
+                }
+                article
+            }
+        })
+        .buffered(CONCURRENT_FETCHES)
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_drops_the_oldest_page_when_full() {
+        remember("https://example.com/0".to_string(), PageMeta::default());
+        std::thread::sleep(Duration::from_millis(2));
+        for i in 1..=CACHE_CAPACITY {
+            remember(format!("https://example.com/{}", i), PageMeta::default());
+        }
+
+        assert!(cache().lock().unwrap().len() <= CACHE_CAPACITY);
+        assert!(cached("https://example.com/0").is_none());
+        assert!(cached(&format!("https://example.com/{}", CACHE_CAPACITY)).is_some());
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{DateTime, Duration, Utc};
+use rocket::http::{ContentType, Status};
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
+use serde::Serialize;
+
+use crate::ids::{permalink, stable_hash};
+use crate::text::markdown_to_html;
+use crate::BlogArticle;
+
+// Entries per feed; readers only care about what is new
+pub const FEED_LENGTH: usize = 50;
+
+// `Last-Modified` and `If-Modified-Since` use the RFC 7231 date format
+const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+// Feeds whose current version is remembered, at most
+const VERSION_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    // Parse a feed's file name; anything else is not a feed, so the caller can answer 404
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        match name {
+            "feed.xml" => Some(FeedFormat::Rss),
+            "atom.xml" => Some(FeedFormat::Atom),
+            "feed.json" => Some(FeedFormat::Json),
+            _ => None,
+        }
+    }
+
+    // Last path segment of the feed, e.g. /category/tech/feed.xml
+    pub fn file_name(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "feed.xml",
+            FeedFormat::Atom => "atom.xml",
+            FeedFormat::Json => "feed.json",
+        }
+    }
+
+    fn content_type(self) -> ContentType {
+        match self {
+            FeedFormat::Rss => ContentType::new("application", "rss+xml"),
+            FeedFormat::Atom => ContentType::new("application", "atom+xml"),
+            FeedFormat::Json => ContentType::new("application", "feed+json"),
+        }
+    }
+}
+
+// One item of a syndication feed
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    // Absolute permalink on this site, also used as the entry's unique ID
+    pub id: String,
+    pub title: String,
+    // The original article
+    pub link: String,
+    pub summary: String,
+    // The article body as HTML
+    pub content_html: String,
+    pub author: Option<String>,
+    pub image: Option<String>,
+    // When the source published it, if it said
+    pub published: Option<DateTime<Utc>>,
+    pub updated: DateTime<Utc>,
+    pub categories: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+impl FeedEntry {
+    pub fn from_article(article: &BlogArticle, base_url: &str) -> Self {
+        FeedEntry {
+            id: format!("{}{}", base_url.trim_end_matches('/'), permalink(&article.id)),
+            title: article.title.clone(),
+            link: article.url.clone(),
+            summary: article.description.clone().unwrap_or_else(|| article.excerpt.clone()),
+            content_html: markdown_to_html(&article.content),
+            author: article.author.clone(),
+            image: article.image_url.clone(),
+            published: article.published_at,
+            updated: article.published_at.unwrap_or(article.fetched_at),
+            categories: article.categories.clone(),
+            tags: article.tags.clone(),
+        }
+    }
+}
+
+// Feed-level details shared by every format
+#[derive(Debug, Clone)]
+pub struct FeedInfo {
+    pub title: String,
+    // Absolute URL of the feed itself
+    pub feed_url: String,
+    // Absolute URL of the page the feed mirrors
+    pub page_url: String,
+}
+
+// Escape text for XML element content and attribute values
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Most recent update among the entries, used as the feed's own timestamp
+pub fn last_updated(entries: &[FeedEntry]) -> Option<DateTime<Utc>> {
+    entries.iter().map(|entry| entry.updated).max()
+}
+
+// Hash of each feed's current body and when that body was first served, by feed URL.
+// Last-Modified comes from here so it moves whenever the body, and with it the ETag, does;
+// entry timestamps miss edits such as refreshed content or a recategorization.
+type Versions = HashMap<String, (u64, DateTime<Utc>)>;
+
+fn versions() -> &'static Mutex<Versions> {
+    static VERSIONS: OnceLock<Mutex<Versions>> = OnceLock::new();
+    VERSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// When the feed at `url` started serving the body with this hash
+fn modified_since(url: &str, hash: u64, now: DateTime<Utc>) -> DateTime<Utc> {
+    let mut versions = versions().lock().unwrap();
+    let since = match versions.get(url) {
+        Some(&(current, since)) if current == hash => return since,
+        // HTTP dates have whole seconds, so a new version must fall in a later second
+        Some(&(_, previous)) => now.max(previous + Duration::seconds(1)),
+        None => now,
+    };
+    if versions.len() >= VERSION_CAPACITY && !versions.contains_key(url) {
+        versions.clear();
+    }
+    versions.insert(url.to_string(), (hash, since));
+    since
+}
+
+// Render an Atom 1.0 document
+pub fn atom(info: &FeedInfo, entries: &[FeedEntry]) -> String {
+    let updated = last_updated(entries).unwrap_or_else(Utc::now);
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <id>{}</id>\n", escape_xml(&info.feed_url)));
+    xml.push_str(&format!("  <title>{}</title>\n", escape_xml(&info.title)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated.to_rfc3339()));
+    xml.push_str(&format!("  <link rel=\"self\" href=\"{}\"/>\n", escape_xml(&info.feed_url)));
+    xml.push_str(&format!("  <link rel=\"alternate\" href=\"{}\"/>\n", escape_xml(&info.page_url)));
+    xml.push_str("  <generator>Blog Engine</generator>\n");
+
+    for entry in entries {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <id>{}</id>\n", escape_xml(&entry.id)));
+        xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&entry.title)));
+        xml.push_str(&format!("    <updated>{}</updated>\n", entry.updated.to_rfc3339()));
+        xml.push_str(&format!("    <link rel=\"alternate\" href=\"{}\"/>\n", escape_xml(&entry.link)));
+        // Atom requires an author on every entry unless the feed has one
+        xml.push_str(&format!(
+            "    <author><name>{}</name></author>\n",
+            escape_xml(entry.author.as_deref().unwrap_or("Unknown"))
+        ));
+        for category in &entry.categories {
+            xml.push_str(&format!("    <category term=\"{}\"/>\n", escape_xml(category)));
+        }
+        xml.push_str(&format!("    <summary>{}</summary>\n", escape_xml(&entry.summary)));
+        if !entry.content_html.is_empty() {
+            xml.push_str(&format!("    <content type=\"html\">{}</content>\n", escape_xml(&entry.content_html)));
+        }
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+// Render an RSS 2.0 document
+pub fn rss(info: &FeedInfo, entries: &[FeedEntry]) -> String {
+    let updated = last_updated(entries).unwrap_or_else(Utc::now);
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str(concat!(
+        "<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" ",
+        "xmlns:content=\"http://purl.org/rss/1.0/modules/content/\" ",
+        "xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n"
+    ));
+    xml.push_str("  <channel>\n");
+    xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&info.title)));
+    xml.push_str(&format!("    <link>{}</link>\n", escape_xml(&info.page_url)));
+    xml.push_str(&format!("    <description>{}</description>\n", escape_xml(&info.title)));
+    xml.push_str(&format!(
+        "    <atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>\n",
+        escape_xml(&info.feed_url)
+    ));
+    xml.push_str(&format!("    <lastBuildDate>{}</lastBuildDate>\n", updated.to_rfc2822()));
+    xml.push_str("    <generator>Blog Engine</generator>\n");
+
+    for entry in entries {
+        xml.push_str("    <item>\n");
+        xml.push_str(&format!("      <title>{}</title>\n", escape_xml(&entry.title)));
+        xml.push_str(&format!("      <link>{}</link>\n", escape_xml(&entry.link)));
+        xml.push_str(&format!("      <guid isPermaLink=\"true\">{}</guid>\n", escape_xml(&entry.id)));
+        xml.push_str(&format!("      <pubDate>{}</pubDate>\n", entry.updated.to_rfc2822()));
+        // RSS wants an email address in <author>, so bylines go in dc:creator
+        if let Some(author) = &entry.author {
+            xml.push_str(&format!("      <dc:creator>{}</dc:creator>\n", escape_xml(author)));
+        }
+        for category in &entry.categories {
+            xml.push_str(&format!("      <category>{}</category>\n", escape_xml(category)));
+        }
+        xml.push_str(&format!("      <description>{}</description>\n", escape_xml(&entry.summary)));
+        if !entry.content_html.is_empty() {
+            xml.push_str(&format!(
+                "      <content:encoded>{}</content:encoded>\n",
+                escape_xml(&entry.content_html)
+            ));
+        }
+        xml.push_str("    </item>\n");
+    }
+    xml.push_str("  </channel>\n");
+    xml.push_str("</rss>\n");
+    xml
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFeed<'a> {
+    version: &'static str,
+    title: &'a str,
+    home_page_url: &'a str,
+    feed_url: &'a str,
+    items: Vec<JsonFeedItem<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFeedItem<'a> {
+    id: &'a str,
+    url: &'a str,
+    title: &'a str,
+    content_html: &'a str,
+    summary: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_published: Option<String>,
+    date_modified: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<JsonFeedAuthor<'a>>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    tags: &'a [String],
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFeedAuthor<'a> {
+    name: &'a str,
+}
+
+// Render a JSON Feed 1.1 document. Optional fields are left out rather than sent as null.
+pub fn json_feed(info: &FeedInfo, entries: &[FeedEntry]) -> String {
+    let feed = JsonFeed {
+        version: "https://jsonfeed.org/version/1.1",
+        title: &info.title,
+        home_page_url: &info.page_url,
+        feed_url: &info.feed_url,
+        items: entries
+            .iter()
+            .map(|entry| JsonFeedItem {
+                id: &entry.id,
+                url: &entry.link,
+                title: &entry.title,
+                content_html: &entry.content_html,
+                summary: &entry.summary,
+                image: entry.image.as_deref(),
+                date_published: entry.published.map(|published| published.to_rfc3339()),
+                date_modified: entry.updated.to_rfc3339(),
+                authors: entry.author.iter().map(|name| JsonFeedAuthor { name }).collect(),
+                tags: &entry.tags,
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&feed).unwrap_or_default()
+}
+
+// A rendered feed. Responds with `ETag` and `Last-Modified`, and with 304 Not Modified
+// when the reader already has this version.
+pub struct Feed {
+    format: FeedFormat,
+    body: String,
+    etag: String,
+    last_modified: DateTime<Utc>,
+}
+
+impl Feed {
+    pub fn new(format: FeedFormat, info: &FeedInfo, entries: &[FeedEntry]) -> Self {
+        let body = match format {
+            FeedFormat::Rss => rss(info, entries),
+            FeedFormat::Atom => atom(info, entries),
+            FeedFormat::Json => json_feed(info, entries),
+        };
+        let hash = stable_hash(&body);
+        Feed {
+            format,
+            etag: format!("\"{:016x}\"", hash),
+            last_modified: modified_since(&info.feed_url, hash, Utc::now()),
+            body,
+        }
+    }
+
+    // Whether the request's conditional headers match this version.
+    // `If-None-Match` wins over `If-Modified-Since` when both are sent.
+    fn is_fresh(&self, request: &Request<'_>) -> bool {
+        if let Some(tags) = request.headers().get_one("If-None-Match") {
+            return tags.split(',').map(str::trim).any(|tag| tag == self.etag || tag == "*");
+        }
+        request
+            .headers()
+            .get_one("If-Modified-Since")
+            .and_then(|since| DateTime::parse_from_rfc2822(since).ok())
+            // HTTP dates have whole seconds
+            .is_some_and(|since| self.last_modified.timestamp() <= since.timestamp())
+    }
+}
+
+impl<'r> Responder<'r, 'static> for Feed {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = if self.is_fresh(request) {
+            Response::build().status(Status::NotModified).finalize()
+        } else {
+            Response::build_from(self.body.respond_to(request)?)
+                .header(self.format.content_type())
+                .finalize()
+        };
+        response.set_raw_header("Last-Modified", self.last_modified.format(HTTP_DATE).to_string());
+        response.set_raw_header("ETag", self.etag);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_article;
+
+    #[test]
+    fn last_modified_moves_with_the_body() {
+        let info = FeedInfo {
+            title: "Test".to_string(),
+            feed_url: "https://example.com/last-modified-test/feed.xml".to_string(),
+            page_url: "https://example.com/".to_string(),
+        };
+        let mut article = test_article("medium:1", "Fresh");
+        article.published_at = Some(Utc::now() - Duration::days(30));
+        let render = |article: &BlogArticle| {
+            Feed::new(FeedFormat::Rss, &info, &[FeedEntry::from_article(article, "https://example.com")])
+        };
+
+        let first = render(&article);
+        let again = render(&article);
+        assert_eq!((&again.etag, again.last_modified), (&first.etag, first.last_modified));
+
+        // Same timestamps, new content: both validators change, and Last-Modified by a whole second
+        article.content = "Updated after the first fetch".to_string();
+        let edited = render(&article);
+        assert_ne!(edited.etag, first.etag);
+        assert!(edited.last_modified.timestamp() > first.last_modified.timestamp());
+        assert_eq!(last_updated(&[FeedEntry::from_article(&article, "")]), article.published_at);
+    }
+}
@@ -0,0 +1,613 @@
+use std::collections::{BTreeMap, HashMap};
+
+use async_graphql::connection::{query, Connection, Edge};
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Enum, InputObject, Object, OutputType, Result, Schema, SimpleObject, ID,
+};
+use async_graphql_rocket::{GraphQLQuery, GraphQLRequest, GraphQLResponse};
+use chrono::{DateTime, Utc};
+use rocket::{Route, State};
+
+use crate::categorize::CategoryEngine;
+use crate::cluster;
+use crate::engagement::Engagement;
+use crate::ids::{permalink, slugify};
+use crate::listing::{ListingParams, SortOrder};
+use crate::mute::Muting;
+use crate::pagination::{DEFAULT_PER_PAGE, MAX_PER_PAGE};
+use crate::ranking::{self, RankingConfig};
+use crate::search::SearchIndex;
+use crate::similarity::Similarity;
+use crate::store::ArticleStore;
+use crate::taxonomy::Taxonomy;
+use crate::text::markdown_to_html;
+use crate::{BlogArticle, SOURCES};
+
+pub type BlogSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+// Deeply nested queries (articles → sources → articles → ...) get expensive fast
+const MAX_DEPTH: usize = 10;
+
+// Fields a query may resolve, counting every item a page of results may hold,
+// e.g. `articles(first: 100)` selecting 20 fields costs about 2000
+const MAX_COMPLEXITY: usize = 5000;
+
+// Build the schema over the same state the HTML routes read
+pub fn schema(
+    store: &ArticleStore,
+    search: &SearchIndex,
+    similarity: &Similarity,
+    engine: &CategoryEngine,
+    taxonomy: &Taxonomy,
+    engagement: &Engagement,
+    ranking: &RankingConfig,
+) -> BlogSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(store.clone())
+        .data(search.clone())
+        .data(similarity.clone())
+        .data(engine.clone())
+        .data(taxonomy.clone())
+        .data(engagement.clone())
+        .data(ranking.clone())
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+// Complexity of a paginated field: its children once per item the page can hold
+fn page_complexity(first: Option<i32>, last: Option<i32>, child_complexity: usize) -> usize {
+    let size = first.or(last).map_or(DEFAULT_PER_PAGE, |size| (size.max(0) as usize).min(MAX_PER_PAGE));
+    size.max(1).saturating_mul(child_complexity)
+}
+
+// The articles a request can see, loaded from the store once and shared by every resolver
+pub struct Snapshot {
+    articles: Vec<BlogArticle>,
+    hidden: Vec<String>,
+    // Visible articles per source slug
+    source_counts: HashMap<String, usize>,
+}
+
+impl Snapshot {
+    pub fn load(store: &ArticleStore, muting: &Muting) -> Self {
+        let (hidden, articles): (Vec<BlogArticle>, Vec<BlogArticle>) =
+            store.all().into_iter().partition(|article| muting.is_hidden(article));
+        let mut source_counts: HashMap<String, usize> = HashMap::new();
+        for article in &articles {
+            for source in &article.sources {
+                *source_counts.entry(slugify(source)).or_default() += 1;
+            }
+        }
+        Snapshot {
+            articles,
+            hidden: hidden.into_iter().map(|article| article.id).collect(),
+            source_counts,
+        }
+    }
+}
+
+fn snapshot<'a>(ctx: &Context<'a>) -> &'a Snapshot {
+    ctx.data_unchecked::<Snapshot>()
+}
+
+// Drop articles the request's mute rules hide
+fn visible(ctx: &Context<'_>, articles: Vec<BlogArticle>) -> Vec<BlogArticle> {
+    match ctx.data_opt::<Muting>() {
+        Some(muting) => muting.visible(articles),
+        None => articles,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Enum)]
+pub enum ArticleOrder {
+    #[default]
+    Newest,
+    Oldest,
+    Popularity,
+    Title,
+    Ranking,
+}
+
+impl From<ArticleOrder> for SortOrder {
+    fn from(order: ArticleOrder) -> Self {
+        match order {
+            ArticleOrder::Newest => SortOrder::Newest,
+            ArticleOrder::Oldest => SortOrder::Oldest,
+            ArticleOrder::Popularity => SortOrder::Popularity,
+            ArticleOrder::Title => SortOrder::Title,
+            ArticleOrder::Ranking => SortOrder::Ranking,
+        }
+    }
+}
+
+// Every field narrows the list; sources match any, the rest must all hold
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct ArticleFilter {
+    // Names or slugs, e.g. "Hacker News" or "hacker-news"
+    sources: Option<Vec<String>>,
+    category: Option<String>,
+    // Also matches tags below it in the taxonomy
+    tag: Option<String>,
+    author: Option<String>,
+    published_after: Option<DateTime<Utc>>,
+    published_before: Option<DateTime<Utc>>,
+}
+
+// Visible articles matching the filter, in the requested order
+fn find_articles(ctx: &Context<'_>, filter: &ArticleFilter, order: ArticleOrder) -> Vec<BlogArticle> {
+    let taxonomy = ctx.data_unchecked::<Taxonomy>();
+    let category = filter.category.as_deref().map(slugify);
+    let tag = filter.tag.as_deref().map(|tag| taxonomy.canonical(tag));
+
+    let articles: Vec<BlogArticle> = snapshot(ctx)
+        .articles
+        .iter()
+        .filter(|article| {
+            let published = article.published_at.unwrap_or(article.fetched_at);
+            category.as_ref().is_none_or(|wanted| article.categories.iter().any(|c| slugify(c) == *wanted))
+                && tag.as_ref().is_none_or(|wanted| article.tags.iter().any(|t| taxonomy.is_within(t, wanted)))
+                && filter.author.as_deref().is_none_or(|wanted| {
+                    article.author.as_deref().is_some_and(|author| author.trim().eq_ignore_ascii_case(wanted.trim()))
+                })
+                && filter.published_after.is_none_or(|after| published >= after)
+                && filter.published_before.is_none_or(|before| published < before)
+        })
+        .cloned()
+        .collect();
+
+    let listing = ListingParams {
+        sort: order.into(),
+        source: filter.sources.clone().unwrap_or_default(),
+        page: None,
+        per_page: None,
+    };
+    listing.apply(articles, ctx.data_unchecked::<RankingConfig>(), ctx.data_unchecked::<Engagement>())
+}
+
+// Extra fields on every connection
+#[derive(SimpleObject)]
+pub struct ConnectionTotal {
+    total_count: usize,
+}
+
+type Page<T> = Connection<usize, T, ConnectionTotal>;
+
+// The [start, end) slice of `total` items a Relay-style page asks for. Cursors are item offsets.
+fn window(total: usize, after: Option<usize>, before: Option<usize>, first: Option<usize>, last: Option<usize>) -> (usize, usize) {
+    // Cursors come from clients, so `after` may be anything up to usize::MAX
+    let mut start = after.map(|after| after.saturating_add(1)).unwrap_or(0).min(total);
+    let mut end = before.unwrap_or(total).min(total).max(start);
+    match (first, last) {
+        (Some(first), _) => end = end.min(start + first.min(MAX_PER_PAGE)),
+        (None, Some(last)) => start = start.max(end.saturating_sub(last.min(MAX_PER_PAGE))),
+        (None, None) => end = end.min(start + DEFAULT_PER_PAGE),
+    }
+    (start, end)
+}
+
+// One page of already listed items
+async fn paginate<T: OutputType>(
+    items: Vec<T>,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> Result<Page<T>> {
+    query(after, before, first, last, |after, before, first, last| async move {
+        let total = items.len();
+        let (start, end) = window(total, after, before, first, last);
+        let mut connection = Connection::with_additional_fields(start > 0, end < total, ConnectionTotal { total_count: total });
+        connection.edges.extend(
+            items
+                .into_iter()
+                .enumerate()
+                .skip(start)
+                .take(end - start)
+                .map(|(offset, item)| Edge::new(offset, item)),
+        );
+        Ok::<_, async_graphql::Error>(connection)
+    })
+    .await
+}
+
+pub struct Article(BlogArticle);
+
+#[Object]
+impl Article {
+    // Stable across refreshes, e.g. "hacker-news:41234567"
+    async fn id(&self) -> ID {
+        ID(self.0.id.clone())
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    // The original article
+    async fn url(&self) -> &str {
+        &self.0.url
+    }
+
+    // Path of the article's page on this site
+    async fn permalink(&self) -> String {
+        permalink(&self.0.id)
+    }
+
+    async fn excerpt(&self) -> &str {
+        &self.0.excerpt
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn content(&self) -> &str {
+        &self.0.content
+    }
+
+    async fn content_html(&self) -> String {
+        markdown_to_html(&self.0.content)
+    }
+
+    async fn author(&self) -> Option<&str> {
+        self.0.author.as_deref()
+    }
+
+    async fn image_url(&self) -> Option<&str> {
+        self.0.image_url.as_deref()
+    }
+
+    async fn language(&self) -> Option<&str> {
+        self.0.language.as_deref()
+    }
+
+    // Upstream popularity, e.g. Hacker News points
+    async fn score(&self) -> Option<i64> {
+        self.0.score
+    }
+
+    async fn comment_count(&self) -> Option<u64> {
+        self.0.comment_count
+    }
+
+    async fn published_at(&self) -> Option<DateTime<Utc>> {
+        self.0.published_at
+    }
+
+    async fn fetched_at(&self) -> DateTime<Utc> {
+        self.0.fetched_at
+    }
+
+    async fn categories(&self) -> &[String] {
+        &self.0.categories
+    }
+
+    async fn tags(&self) -> &[String] {
+        &self.0.tags
+    }
+
+    async fn keywords(&self) -> &[String] {
+        &self.0.keywords
+    }
+
+    // Every source the story was seen on
+    async fn sources(&self) -> Vec<Source> {
+        self.0.sources.iter().map(|name| Source { name: name.clone() }).collect()
+    }
+
+    // Similar articles from other sources, most similar first
+    async fn related(&self, ctx: &Context<'_>) -> Vec<Article> {
+        let store = ctx.data_unchecked::<ArticleStore>();
+        let related = ctx.data_unchecked::<Similarity>().related(&self.0.id, store).unwrap_or_default();
+        let articles = related.iter().filter_map(|related| store.get(&related.id)).collect();
+        visible(ctx, articles).into_iter().map(Article).collect()
+    }
+}
+
+pub struct Source {
+    name: String,
+}
+
+#[Object]
+impl Source {
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn slug(&self) -> String {
+        slugify(&self.name)
+    }
+
+    async fn article_count(&self, ctx: &Context<'_>) -> usize {
+        snapshot(ctx).source_counts.get(&slugify(&self.name)).copied().unwrap_or(0)
+    }
+
+    #[graphql(complexity = "page_complexity(first, last, child_complexity)")]
+    async fn articles(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] order_by: ArticleOrder,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Page<Article>> {
+        let filter = ArticleFilter {
+            sources: Some(vec![self.name.clone()]),
+            ..ArticleFilter::default()
+        };
+        let articles = find_articles(ctx, &filter, order_by).into_iter().map(Article).collect();
+        paginate(articles, after, before, first, last).await
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Category {
+    name: String,
+    slug: String,
+    article_count: usize,
+}
+
+#[derive(SimpleObject)]
+pub struct Tag {
+    name: String,
+    // The broader topic in the taxonomy, if any
+    parent: Option<String>,
+    article_count: usize,
+}
+
+// One story as seen across sources, as on the front page
+pub struct Story(cluster::StoryCluster);
+
+#[Object]
+impl Story {
+    // The best article covering the story
+    async fn representative(&self) -> Article {
+        Article(self.0.representative.clone())
+    }
+
+    // Other articles repeating it
+    async fn related(&self) -> Vec<Article> {
+        self.0.related.iter().cloned().map(Article).collect()
+    }
+}
+
+pub struct SearchResult {
+    article: BlogArticle,
+    score: f32,
+    title_html: String,
+    snippet_html: String,
+}
+
+#[Object]
+impl SearchResult {
+    async fn article(&self) -> Article {
+        Article(self.article.clone())
+    }
+
+    async fn score(&self) -> f32 {
+        self.score
+    }
+
+    // Matched terms are wrapped in <b>; everything else is escaped
+    async fn title_html(&self) -> &str {
+        &self.title_html
+    }
+
+    async fn snippet_html(&self) -> &str {
+        &self.snippet_html
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    // Each parameter is a GraphQL argument
+    #[allow(clippy::too_many_arguments)]
+    #[graphql(complexity = "page_complexity(first, last, child_complexity)")]
+    async fn articles(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: ArticleFilter,
+        #[graphql(default)] order_by: ArticleOrder,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Page<Article>> {
+        let articles = find_articles(ctx, &filter, order_by).into_iter().map(Article).collect();
+        paginate(articles, after, before, first, last).await
+    }
+
+    async fn article(&self, ctx: &Context<'_>, id: ID) -> Option<Article> {
+        ctx.data_unchecked::<ArticleStore>().get(&id).map(Article)
+    }
+
+    // Scraped sources and every source articles were seen on
+    async fn sources(&self, ctx: &Context<'_>) -> Vec<Source> {
+        let mut names: BTreeMap<String, String> = BTreeMap::new();
+        let seen = snapshot(ctx).articles.iter().flat_map(|article| article.sources.iter().cloned());
+        for name in SOURCES.iter().map(|source| source.to_string()).chain(seen) {
+            names.entry(slugify(&name)).or_insert(name);
+        }
+        names.into_values().map(|name| Source { name }).collect()
+    }
+
+    // By name or slug
+    async fn source(&self, ctx: &Context<'_>, name: String) -> Option<Source> {
+        let slug = slugify(&name);
+        self.sources(ctx).await.ok()?.into_iter().find(|source| slugify(&source.name) == slug)
+    }
+
+    async fn categories(&self, ctx: &Context<'_>) -> Vec<Category> {
+        let engine = ctx.data_unchecked::<CategoryEngine>();
+        let mut counts: BTreeMap<String, usize> = engine.known_categories().into_iter().map(|c| (c, 0)).collect();
+        for article in &snapshot(ctx).articles {
+            for category in &article.categories {
+                *counts.entry(category.clone()).or_default() += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(name, article_count)| Category { slug: slugify(&name), name, article_count })
+            .collect()
+    }
+
+    // Tags in use, most used first
+    async fn tags(&self, ctx: &Context<'_>) -> Vec<Tag> {
+        let taxonomy = ctx.data_unchecked::<Taxonomy>();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for article in &snapshot(ctx).articles {
+            for tag in &article.tags {
+                *counts.entry(tag.clone()).or_default() += 1;
+            }
+        }
+        let mut tags: Vec<Tag> = counts
+            .into_iter()
+            .map(|(name, article_count)| Tag {
+                parent: taxonomy.parent(&name).map(str::to_string),
+                name,
+                article_count,
+            })
+            .collect();
+        tags.sort_by(|a, b| b.article_count.cmp(&a.article_count).then_with(|| a.name.cmp(&b.name)));
+        tags
+    }
+
+    // Front-page stories: near-duplicates grouped, best ranked first
+    #[graphql(complexity = "page_complexity(first, last, child_complexity)")]
+    async fn stories(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Page<Story>> {
+        let mut articles = snapshot(ctx).articles.clone();
+        ranking::rank(
+            &mut articles,
+            ctx.data_unchecked::<RankingConfig>(),
+            ctx.data_unchecked::<Engagement>(),
+            Utc::now(),
+        );
+        articles.truncate(cluster::MAX_ARTICLES);
+        let stories = cluster::cluster_articles(articles).into_iter().map(Story).collect();
+        paginate(stories, after, before, first, last).await
+    }
+
+    // Full-text search in the same syntax as the search page, best matches first
+    #[graphql(complexity = "page_complexity(first, last, child_complexity)")]
+    async fn search(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Page<SearchResult>> {
+        let store = ctx.data_unchecked::<ArticleStore>();
+        let index = ctx.data_unchecked::<SearchIndex>();
+        let hidden = &snapshot(ctx).hidden;
+
+        self::query(after, before, first, last, |after, before, first, last| async move {
+            // The index pages by offset, so count the matches before fetching the window
+            let total = index.count(&query, hidden);
+            let (start, end) = window(total, after, before, first, last);
+            let hits = if end > start { index.search(&query, end - start, start, hidden, store).hits } else { Vec::new() };
+            let mut connection = Connection::with_additional_fields(start > 0, end < total, ConnectionTotal { total_count: total });
+            connection.edges.extend(hits.into_iter().zip(start..).filter_map(|(hit, offset)| {
+                let article = store.get(&hit.id)?;
+                Some(Edge::new(
+                    offset,
+                    SearchResult {
+                        article,
+                        score: hit.score,
+                        title_html: hit.title_html,
+                        snippet_html: hit.snippet_html,
+                    },
+                ))
+            }));
+            Ok::<_, async_graphql::Error>(connection)
+        })
+        .await
+    }
+}
+
+#[post("/graphql", data = "<request>")]
+async fn graphql_request(
+    schema: &State<BlogSchema>,
+    request: GraphQLRequest,
+    store: &State<ArticleStore>,
+    muting: Muting,
+) -> GraphQLResponse {
+    request.data(Snapshot::load(store, &muting)).data(muting).execute(schema.inner()).await
+}
+
+// `GET /graphql?query=...`, for quick queries and caches
+#[get("/graphql?<query..>")]
+async fn graphql_query(
+    schema: &State<BlogSchema>,
+    query: GraphQLQuery,
+    store: &State<ArticleStore>,
+    muting: Muting,
+) -> GraphQLResponse {
+    GraphQLRequest::from(query).data(Snapshot::load(store, &muting)).data(muting).execute(schema.inner()).await
+}
+
+// GraphiQL, for exploring the schema while developing
+#[cfg(debug_assertions)]
+#[get("/graphql", rank = 2)]
+fn graphiql() -> rocket::response::content::RawHtml<String> {
+    rocket::response::content::RawHtml(async_graphql::http::GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+pub fn routes() -> Vec<Route> {
+    #[cfg_attr(not(debug_assertions), allow(unused_mut))]
+    let mut routes = routes![graphql_request, graphql_query];
+    #[cfg(debug_assertions)]
+    routes.extend(routes![graphiql]);
+    routes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_defaults_to_the_first_page() {
+        assert_eq!(window(50, None, None, None, None), (0, DEFAULT_PER_PAGE));
+        assert_eq!(window(5, None, None, None, None), (0, 5));
+        assert_eq!(window(0, None, None, None, None), (0, 0));
+    }
+
+    #[test]
+    fn window_pages_forward_after_a_cursor() {
+        assert_eq!(window(50, Some(9), None, Some(10), None), (10, 20));
+        assert_eq!(window(50, Some(45), None, Some(10), None), (46, 50));
+        assert_eq!(window(50, Some(49), None, Some(10), None), (50, 50));
+    }
+
+    #[test]
+    fn window_pages_backward_before_a_cursor() {
+        assert_eq!(window(50, None, Some(20), None, Some(5)), (15, 20));
+        assert_eq!(window(50, None, Some(3), None, Some(5)), (0, 3));
+        assert_eq!(window(50, None, None, None, Some(5)), (45, 50));
+    }
+
+    #[test]
+    fn window_caps_the_page_size() {
+        assert_eq!(window(1000, None, None, Some(10_000), None), (0, MAX_PER_PAGE));
+        assert_eq!(window(1000, None, None, None, Some(10_000)), (1000 - MAX_PER_PAGE, 1000));
+    }
+
+    #[test]
+    fn window_survives_out_of_range_cursors() {
+        assert_eq!(window(50, Some(usize::MAX), None, Some(10), None), (50, 50));
+        assert_eq!(window(50, None, Some(usize::MAX), None, Some(10)), (40, 50));
+        assert_eq!(window(50, Some(30), Some(10), Some(10), None), (31, 31));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::dedup;
+
+// 64-bit FNV-1a, stable across builds and platforms unlike std's randomized hashers
+pub fn stable_hash(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+// Lowercase, URL-safe form of a name: "The Guardian" -> "the-guardian", "Dev.to" -> "dev-to"
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+fn is_safe_segment(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 64
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// Globally unique, stable article ID of the form "<source-slug>:<local-id>".
+// The upstream ID is used when it is a plain token; otherwise the canonical URL is hashed,
+// or the title when there is no URL either, so such articles do not all share one ID.
+pub fn article_id(source: &str, upstream_id: Option<&str>, url: &str, title: &str) -> String {
+    let local = match upstream_id.map(str::trim) {
+        Some(id) if is_safe_segment(id) => id.to_string(),
+        other => {
+            let key = dedup::canonical_url(url)
+                .or_else(|| other.filter(|id| !id.is_empty()).map(str::to_string))
+                .or_else(|| Some(url.trim()).filter(|url| !url.is_empty()).map(str::to_string))
+                .unwrap_or_else(|| format!("{}\n{}", source, title.trim()));
+            format!("{:016x}", stable_hash(&key))
+        }
+    };
+    format!("{}:{}", slugify(source), local)
+}
+
+// Split an article ID back into the (source, id) pair used by the permalink route
+pub fn split_id(id: &str) -> Option<(&str, &str)> {
+    id.split_once(':')
+}
+
+// Path of the page showing a stored article
+pub fn permalink(id: &str) -> String {
+    match split_id(id) {
+        Some((source, local)) => format!("/article/{}/{}", source, local),
+        None => format!("/article/unknown/{}", id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_upstream_ids_are_kept() {
+        assert_eq!(article_id("Hacker News", Some("4242"), "", "Ask HN"), "hacker-news:4242");
+    }
+
+    #[test]
+    fn urls_are_hashed_canonically() {
+        let a = article_id("Medium", None, "https://medium.com/p/rust?utm_source=x", "Rust");
+        let b = article_id("Medium", None, "https://www.medium.com/p/rust/", "Rust, again");
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn articles_without_a_url_get_distinct_ids() {
+        let a = article_id("Medium", None, "", "First post");
+        let b = article_id("Medium", None, "  ", "Second post");
+        assert_ne!(a, b);
+        assert_eq!(a, article_id("Medium", None, "", "First post"));
+    }
+}
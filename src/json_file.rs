@@ -0,0 +1,100 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// A value persisted as one JSON file, for the stores kept under the data directory.
+// Failures are logged with `label` rather than returned: a store that cannot be read
+// starts empty, and one that cannot be written keeps working from memory.
+#[derive(Debug)]
+pub struct JsonFile<T> {
+    path: PathBuf,
+    label: &'static str,
+    pretty: bool,
+    value: PhantomData<fn() -> T>,
+}
+
+// Not derived, which would require `T: Clone` though no value is held
+impl<T> Clone for JsonFile<T> {
+    fn clone(&self) -> Self {
+        JsonFile {
+            path: self.path.clone(),
+            label: self.label,
+            pretty: self.pretty,
+            value: PhantomData,
+        }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> JsonFile<T> {
+    pub fn new(path: PathBuf, label: &'static str) -> Self {
+        JsonFile {
+            path,
+            label,
+            pretty: false,
+            value: PhantomData,
+        }
+    }
+
+    // Indent the file, for stores people are expected to read or edit by hand
+    pub fn pretty(mut self) -> Self {
+        self.pretty = true;
+        self
+    }
+
+    // The stored value, or None if the file is missing or unreadable
+    pub fn load(&self) -> Option<T> {
+        let json = fs::read_to_string(&self.path).ok()?;
+        serde_json::from_str(&json)
+            .map_err(|e| eprintln!("Failed to parse {} {}: {}", self.label, self.path.display(), e))
+            .ok()
+    }
+
+    // Write the value, replacing the previous file atomically
+    pub fn save(&self, value: &T) {
+        let json = match if self.pretty { serde_json::to_string_pretty(value) } else { serde_json::to_string(value) } {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("Failed to serialize {}: {}", self.label, e);
+                return;
+            }
+        };
+
+        if let Some(parent) = self.path.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                eprintln!("Failed to create {}: {}", parent.display(), e);
+                return;
+            }
+        }
+
+        let temp_path = self.path.with_extension("json.tmp");
+        if let Err(e) = fs::write(&temp_path, json).and_then(|_| fs::rename(&temp_path, &self.path)) {
+            eprintln!("Failed to save {} {}: {}", self.label, self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn saves_and_loads_back() {
+        let dir = std::env::temp_dir().join(format!("json-file-{}", std::process::id()));
+        let file: JsonFile<HashMap<String, u64>> = JsonFile::new(dir.join("counts.json"), "counts");
+        assert_eq!(file.load(), None);
+
+        let counts = HashMap::from([("hacker-news:1".to_string(), 3)]);
+        file.save(&counts);
+        assert_eq!(file.load(), Some(counts));
+        assert!(!dir.join("counts.json.tmp").exists());
+
+        fs::write(dir.join("counts.json"), "not json").unwrap();
+        assert_eq!(file.load(), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
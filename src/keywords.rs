@@ -0,0 +1,192 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Deserialize;
+
+use crate::ids::slugify;
+use crate::text::{is_stopword, strip_html, words};
+use crate::BlogArticle;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct KeywordConfig {
+    // Keywords kept per article
+    pub count: usize,
+    // Longest phrase considered, in words
+    pub max_ngram: usize,
+    // Ignore terms found in more than this share of the corpus
+    pub max_document_ratio: f64,
+    // Added to the built-in stopword list
+    pub stopwords: Vec<String>,
+}
+
+impl Default for KeywordConfig {
+    fn default() -> Self {
+        KeywordConfig {
+            count: 5,
+            max_ngram: 2,
+            max_document_ratio: 0.5,
+            stopwords: Vec::new(),
+        }
+    }
+}
+
+// TF-IDF keyword extraction against the stored corpus
+#[derive(Debug, Clone)]
+pub struct KeywordExtractor {
+    config: KeywordConfig,
+    stopwords: HashSet<String>,
+}
+
+impl KeywordExtractor {
+    pub fn new(config: &KeywordConfig) -> Self {
+        KeywordExtractor {
+            config: config.clone(),
+            stopwords: config.stopwords.iter().map(|word| word.to_lowercase()).collect(),
+        }
+    }
+
+    fn is_noise(&self, word: &str) -> bool {
+        word.chars().count() < 3
+            || word.chars().all(|c| c.is_ascii_digit())
+            || is_stopword(word)
+            || self.stopwords.contains(word)
+    }
+
+    // Term counts for one article: words and phrases that neither start nor end with noise
+    fn term_counts(&self, article: &BlogArticle) -> HashMap<String, usize> {
+        let text = format!(
+            "{} {} {} {}",
+            article.title,
+            article.excerpt,
+            article.description.as_deref().unwrap_or(""),
+            strip_html(&article.content)
+        );
+        let all_words = words(&text);
+        let mut counts: HashMap<String, usize> = HashMap::new();
+
+        for n in 1..=self.config.max_ngram.max(1) {
+            for window in all_words.windows(n) {
+                if self.is_noise(&window[0]) || self.is_noise(&window[n - 1]) {
+                    continue;
+                }
+                *counts.entry(window.join(" ")).or_default() += 1;
+            }
+        }
+        // The title says what the article is about, so its terms get an extra vote
+        for word in words(&article.title) {
+            if let Some(count) = counts.get_mut(&word) {
+                *count += 1;
+            }
+        }
+        counts
+    }
+
+    // Top keywords for every article in the corpus, keyed by article ID
+    pub fn extract(&self, corpus: &[BlogArticle]) -> HashMap<String, Vec<String>> {
+        let counts: Vec<HashMap<String, usize>> = corpus.iter().map(|a| self.term_counts(a)).collect();
+
+        let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+        for terms in &counts {
+            for term in terms.keys() {
+                *document_frequency.entry(term.as_str()).or_default() += 1;
+            }
+        }
+
+        let documents = corpus.len() as f64;
+        let max_documents = (documents * self.config.max_document_ratio).max(1.0);
+
+        corpus
+            .iter()
+            .zip(&counts)
+            .map(|(article, terms)| {
+                let length: usize = terms.values().sum();
+                let mut scored: Vec<(&String, f64)> = terms
+                    .iter()
+                    .filter(|(term, _)| {
+                        // Terms common to most of the corpus only make sense in a tiny corpus
+                        documents < 4.0 || (document_frequency[term.as_str()] as f64) <= max_documents
+                    })
+                    .map(|(term, &count)| {
+                        let tf = count as f64 / length.max(1) as f64;
+                        let idf = ((documents + 1.0) / (document_frequency[term.as_str()] as f64 + 1.0)).ln() + 1.0;
+                        (term, tf * idf)
+                    })
+                    .collect();
+                scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+                (article.id.clone(), self.select(article, scored))
+            })
+            .collect()
+    }
+
+    // Best-scoring terms that repeat neither a source tag nor an already chosen keyword
+    fn select(&self, article: &BlogArticle, scored: Vec<(&String, f64)>) -> Vec<String> {
+        let tags: HashSet<String> = article.tags.iter().map(|tag| slugify(tag)).collect();
+        let mut chosen: Vec<String> = Vec::with_capacity(self.config.count);
+
+        for (term, _) in scored {
+            if chosen.len() >= self.config.count {
+                break;
+            }
+            let overlaps = chosen
+                .iter()
+                .any(|c| c.split(' ').any(|word| term.split(' ').any(|other| other == word)));
+            if !overlaps && !tags.contains(&slugify(term)) {
+                chosen.push(term.clone());
+            }
+        }
+        chosen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_article;
+
+    fn corpus() -> Vec<BlogArticle> {
+        [
+            "Kubernetes operators for software teams",
+            "Postgres vacuum tuning for software teams",
+            "Rust borrow checker explained for software teams",
+            "Software teams adopt WebAssembly plugins",
+            "Software estimates and the planning fallacy",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, title)| test_article(&format!("medium:{}", i), title))
+        .collect()
+    }
+
+    #[test]
+    fn distinctive_terms_beat_words_common_to_the_corpus() {
+        let config = KeywordConfig { count: 3, ..KeywordConfig::default() };
+        let keywords = KeywordExtractor::new(&config).extract(&corpus());
+
+        let postgres = &keywords["medium:1"];
+        assert_eq!(postgres.len(), 3);
+        assert!(postgres.iter().any(|k| k.contains("postgres")));
+        // "software" and "teams" appear in every article, "for" is a stopword
+        for words in keywords.values() {
+            assert!(words.iter().all(|k| !k.contains("software") && !k.contains("teams") && !k.starts_with("for")));
+        }
+    }
+
+    #[test]
+    fn tags_configured_stopwords_and_repeated_words_are_skipped() {
+        let mut articles = corpus();
+        articles[2].tags = vec!["Rust".to_string()];
+        let config = KeywordConfig { stopwords: vec!["Explained".to_string()], ..KeywordConfig::default() };
+        let keywords = KeywordExtractor::new(&config).extract(&articles);
+
+        let rust = &keywords["medium:2"];
+        assert!(!rust.contains(&"rust".to_string()));
+        assert!(rust.iter().all(|k| !k.contains("explained")));
+        // Each word is used by at most one chosen keyword
+        let mut words: Vec<&str> = rust.iter().flat_map(|k| k.split(' ')).collect();
+        let total = words.len();
+        words.sort();
+        words.dedup();
+        assert_eq!(words.len(), total);
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::Utc;
+use rocket::http::RawStr;
+use serde::Serialize;
+
+use crate::engagement::Engagement;
+use crate::pagination::PageParams;
+use crate::ids::slugify;
+use crate::ranking::{self, RankingConfig};
+use crate::BlogArticle;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromFormField, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Newest,
+    Oldest,
+    Popularity,
+    Title,
+    Ranking,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 5] = [
+        SortOrder::Newest,
+        SortOrder::Oldest,
+        SortOrder::Popularity,
+        SortOrder::Title,
+        SortOrder::Ranking,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortOrder::Newest => "newest",
+            SortOrder::Oldest => "oldest",
+            SortOrder::Popularity => "popularity",
+            SortOrder::Title => "title",
+            SortOrder::Ranking => "ranking",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortOrder::Newest => "Newest First",
+            SortOrder::Oldest => "Oldest First",
+            SortOrder::Popularity => "Most Popular",
+            SortOrder::Title => "Title (A-Z)",
+            SortOrder::Ranking => "Top Ranked",
+        }
+    }
+}
+
+// Choice offered by the "Sort By" select box
+#[derive(Debug, Serialize)]
+pub struct SortOption {
+    pub value: &'static str,
+    pub label: &'static str,
+    pub selected: bool,
+}
+
+// `?sort=&source=&source=&page=&per_page=` on listing routes. An unknown sort order is
+// rejected; sources are matched by slug, so "Hacker News" and "hacker-news" both work.
+// Rocket allows one trailing query parameter per route, so paging rides along here.
+#[derive(Debug, Clone, Default, FromForm)]
+pub struct ListingParams {
+    #[field(default = SortOrder::Newest)]
+    pub sort: SortOrder,
+    pub source: Vec<String>,
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+}
+
+impl ListingParams {
+    pub fn paging(&self) -> PageParams {
+        PageParams {
+            page: self.page,
+            per_page: self.per_page,
+        }
+    }
+
+    // Keep only articles carried by one of the requested sources, then sort them
+    pub fn apply(
+        &self,
+        articles: Vec<BlogArticle>,
+        ranking: &RankingConfig,
+        engagement: &Engagement,
+    ) -> Vec<BlogArticle> {
+        let sources = self.source_slugs();
+        let mut articles: Vec<BlogArticle> = articles
+            .into_iter()
+            .filter(|article| sources.is_empty() || article.sources.iter().any(|s| sources.contains(&slugify(s))))
+            .collect();
+
+        // Articles arrive newest first from the store, and every sort below is stable
+        match self.sort {
+            SortOrder::Newest => {}
+            SortOrder::Oldest => articles.reverse(),
+            SortOrder::Popularity => articles.sort_by_key(|article| std::cmp::Reverse(ranking::popularity(article))),
+            SortOrder::Title => articles.sort_by_key(|article| article.title.to_lowercase()),
+            SortOrder::Ranking => ranking::rank(&mut articles, ranking, engagement, Utc::now()),
+        }
+        articles
+    }
+
+    // Slugs of the requested sources, for matching and for marking them selected
+    pub fn source_slugs(&self) -> Vec<String> {
+        self.source.iter().map(|source| slugify(source)).filter(|slug| !slug.is_empty()).collect()
+    }
+
+    pub fn sort_options(&self) -> Vec<SortOption> {
+        SortOrder::ALL
+            .into_iter()
+            .map(|order| SortOption {
+                value: order.as_str(),
+                label: order.label(),
+                selected: order == self.sort,
+            })
+            .collect()
+    }
+
+    // The parameters as a query string, so page links keep the current filters, e.g. "sort=title&source=medium"
+    pub fn query_string(&self) -> String {
+        let mut pairs: Vec<String> = Vec::new();
+        if self.sort != SortOrder::default() {
+            pairs.push(format!("sort={}", self.sort.as_str()));
+        }
+        for source in &self.source {
+            pairs.push(format!("source={}", RawStr::new(source).percent_encode()));
+        }
+        pairs.join("&")
+    }
+
+    // `path` with the parameters appended, ready for pagination links
+    pub fn url(&self, path: &str) -> String {
+        match self.query_string() {
+            query if query.is_empty() => path.to_string(),
+            query => format!("{}?{}", path, query),
+        }
+    }
+}
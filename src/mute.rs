@@ -0,0 +1,325 @@
+use regex::{Regex, RegexBuilder};
+use reqwest::Url;
+use rocket::http::{Cookie, CookieJar};
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest, Request};
+use rocket::State;
+use serde::{Deserialize, Serialize};
+
+use crate::categorize::host_matches;
+use crate::ids::slugify;
+use crate::taxonomy::Taxonomy;
+use crate::BlogArticle;
+
+// Cookie holding a reader's own mute rules as JSON
+const COOKIE: &str = "muted";
+
+// Each list in the cookie with the kind of rule its entries are
+const KINDS: &[(&str, &str)] = &[
+    ("keywords", "keyword"),
+    ("patterns", "pattern"),
+    ("tags", "tag"),
+    ("sources", "source"),
+    ("domains", "domain"),
+    ("authors", "author"),
+];
+
+// Things to hide. Global rules come from Rocket.toml; personal ones live in a cookie.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MuteRules {
+    // Whole words or phrases in the title, excerpt or description, case-insensitive
+    pub keywords: Vec<String>,
+    // Regexes over the same text as keywords
+    pub patterns: Vec<String>,
+    pub tags: Vec<String>,
+    // A story is only hidden when every source carrying it is muted
+    pub sources: Vec<String>,
+    // Matched against the URL host and its parent domains, ignoring "www."
+    pub domains: Vec<String>,
+    // Upstream usernames or bylines, case-insensitive
+    pub authors: Vec<String>,
+}
+
+impl MuteRules {
+    fn list_mut(&mut self, kind: &str) -> Option<&mut Vec<String>> {
+        match kind {
+            "keyword" => Some(&mut self.keywords),
+            "pattern" => Some(&mut self.patterns),
+            "tag" => Some(&mut self.tags),
+            "source" => Some(&mut self.sources),
+            "domain" => Some(&mut self.domains),
+            "author" => Some(&mut self.authors),
+            _ => None,
+        }
+    }
+
+    // Add a rule of the given kind ("keyword", "pattern", "tag", "source", "domain" or "author")
+    pub fn add(&mut self, kind: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err("A mute rule needs a value".to_string());
+        }
+        if kind == "pattern" {
+            Regex::new(value).map_err(|e| format!("Invalid pattern: {}", e))?;
+        }
+        let list = self.list_mut(kind).ok_or_else(|| format!("Unknown kind of mute rule \"{}\"", kind))?;
+        if !list.iter().any(|existing| existing == value) {
+            list.push(value.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, kind: &str, value: &str) {
+        if let Some(list) = self.list_mut(kind) {
+            list.retain(|existing| existing != value.trim());
+        }
+    }
+
+    // Personal rules from the request's cookie; a missing cookie means none
+    pub fn from_cookies(cookies: &CookieJar<'_>) -> Self {
+        cookies.get(COOKIE).map(|cookie| MuteRules::parse(cookie.value())).unwrap_or_default()
+    }
+
+    // Rules from the cookie's JSON, checked one by one: the cookie is client-controlled, and
+    // one bad entry should cost the reader that rule rather than all of them
+    fn parse(json: &str) -> Self {
+        let mut rules = MuteRules::default();
+        let value: serde_json::Value = match serde_json::from_str(json) {
+            Ok(value) => value,
+            Err(e) => {
+                eprintln!("Ignoring unreadable mute cookie: {}", e);
+                return rules;
+            }
+        };
+        for (field, kind) in KINDS {
+            let Some(entries) = value.get(field).and_then(|entries| entries.as_array()) else {
+                continue;
+            };
+            for entry in entries {
+                let added = match entry.as_str() {
+                    Some(entry) => rules.add(kind, entry),
+                    None => Err(format!("not a string: {}", entry)),
+                };
+                if let Err(e) = added {
+                    eprintln!("Ignoring muted {} from cookie: {}", kind, e);
+                }
+            }
+        }
+        rules
+    }
+
+    pub fn save_to_cookies(&self, cookies: &CookieJar<'_>) {
+        if *self == MuteRules::default() {
+            cookies.remove(Cookie::from(COOKIE));
+            return;
+        }
+        let json = serde_json::to_string(self).unwrap_or_default();
+        cookies.add(Cookie::build((COOKIE, json)).path("/").permanent());
+    }
+}
+
+// Why an article is hidden, e.g. rule "domain: example.com" from the "global" rules
+#[derive(Debug, Clone, Serialize)]
+pub struct MuteReason {
+    pub scope: &'static str,
+    pub rule: String,
+}
+
+// Mute rules compiled for matching
+#[derive(Debug, Clone, Default)]
+pub struct MuteFilter {
+    rules: MuteRules,
+    keywords: Vec<(String, Regex)>,
+    patterns: Vec<(String, Regex)>,
+    // Each muted tag as written, with the canonical tags it covers: itself and everything below it
+    tags: Vec<(String, Vec<String>)>,
+}
+
+impl MuteFilter {
+    pub fn new(rules: &MuteRules, taxonomy: &Taxonomy) -> Result<Self, String> {
+        let keywords = rules
+            .keywords
+            .iter()
+            .map(|keyword| {
+                RegexBuilder::new(&format!(r"\b{}\b", regex::escape(keyword.trim())))
+                    .case_insensitive(true)
+                    .build()
+                    .map(|regex| (keyword.clone(), regex))
+                    .map_err(|e| format!("muted keyword '{}': {}", keyword, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let patterns = rules
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map(|regex| (pattern.clone(), regex))
+                    .map_err(|e| format!("muted pattern '{}': {}", pattern, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let tags = rules
+            .tags
+            .iter()
+            .map(|tag| {
+                let mut covered = vec![taxonomy.canonical(tag)];
+                let mut next = 0;
+                while next < covered.len() {
+                    for child in taxonomy.children(&covered[next]) {
+                        if !covered.contains(&child) {
+                            covered.push(child);
+                        }
+                    }
+                    next += 1;
+                }
+                (tag.clone(), covered)
+            })
+            .collect();
+
+        Ok(MuteFilter { rules: rules.clone(), keywords, patterns, tags })
+    }
+
+    pub fn rules(&self) -> &MuteRules {
+        &self.rules
+    }
+
+    // The first rule that hides the article, if any
+    pub fn matching_rule(&self, article: &BlogArticle) -> Option<String> {
+        let rules = &self.rules;
+        let text = format!(
+            "{}\n{}\n{}",
+            article.title,
+            article.excerpt,
+            article.description.as_deref().unwrap_or("")
+        );
+
+        if let Some((keyword, _)) = self.keywords.iter().find(|(_, regex)| regex.is_match(&text)) {
+            return Some(format!("keyword: {}", keyword));
+        }
+        if let Some((pattern, _)) = self.patterns.iter().find(|(_, regex)| regex.is_match(&text)) {
+            return Some(format!("pattern: {}", pattern));
+        }
+        if let Some((tag, _)) = self.tags.iter().find(|(_, covered)| article.tags.iter().any(|t| covered.contains(t))) {
+            return Some(format!("tag: {}", tag));
+        }
+        let muted_source = |source: &String| rules.sources.iter().any(|muted| slugify(muted) == slugify(source));
+        if !article.sources.is_empty() && article.sources.iter().all(muted_source) {
+            return Some(format!("source: {}", article.sources.join(", ")));
+        }
+        let host = Url::parse(&article.url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.trim_start_matches("www.").to_lowercase()));
+        if let Some(domain) = host.and_then(|host| rules.domains.iter().find(|domain| host_matches(&host, domain))) {
+            return Some(format!("domain: {}", domain));
+        }
+        if let Some(author) = article.author.as_deref().and_then(|author| {
+            rules.authors.iter().find(|muted| muted.trim().eq_ignore_ascii_case(author.trim()))
+        }) {
+            return Some(format!("author: {}", author));
+        }
+        None
+    }
+}
+
+// Global and personal mute rules of the current request
+pub struct Muting {
+    pub global: MuteFilter,
+    pub personal: MuteFilter,
+}
+
+impl Muting {
+    pub fn reason(&self, article: &BlogArticle) -> Option<MuteReason> {
+        let global = self.global.matching_rule(article).map(|rule| MuteReason { scope: "global", rule });
+        global.or_else(|| self.personal.matching_rule(article).map(|rule| MuteReason { scope: "personal", rule }))
+    }
+
+    pub fn is_hidden(&self, article: &BlogArticle) -> bool {
+        self.reason(article).is_some()
+    }
+
+    // Drop hidden articles, keeping the order of the rest
+    pub fn visible(&self, articles: Vec<BlogArticle>) -> Vec<BlogArticle> {
+        articles.into_iter().filter(|article| !self.is_hidden(article)).collect()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Muting {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, ()> {
+        let global = match request.guard::<&State<MuteFilter>>().await {
+            Outcome::Success(global) => global.inner().clone(),
+            _ => MuteFilter::default(),
+        };
+        let fallback = Taxonomy::default();
+        let taxonomy = match request.guard::<&State<Taxonomy>>().await {
+            Outcome::Success(taxonomy) => taxonomy.inner(),
+            _ => &fallback,
+        };
+        // Cookie rules are validated as they are parsed, so compiling them should not fail
+        let personal = MuteFilter::new(&MuteRules::from_cookies(request.cookies()), taxonomy).unwrap_or_default();
+        Outcome::Success(Muting { global, personal })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taxonomy::{TagDefinition, TaxonomyConfig};
+    use crate::test_article;
+
+    fn tag(name: &str, parent: Option<&str>, aliases: &[&str]) -> TagDefinition {
+        TagDefinition {
+            name: name.to_string(),
+            parent: parent.map(str::to_string),
+            aliases: aliases.iter().map(|alias| alias.to_string()).collect(),
+        }
+    }
+
+    fn tagged(id: &str, tags: &[&str]) -> BlogArticle {
+        let mut article = test_article(id, "Untitled");
+        article.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        article
+    }
+
+    #[test]
+    fn tag_mutes_follow_the_taxonomy() {
+        let taxonomy = Taxonomy::new(&TaxonomyConfig {
+            tags: vec![
+                tag("programming", None, &[]),
+                tag("rust", Some("programming"), &["rustlang"]),
+                tag("python", Some("programming"), &[]),
+            ],
+            ..TaxonomyConfig::default()
+        });
+        let muting = |tag: &str| {
+            MuteFilter::new(&MuteRules { tags: vec![tag.to_string()], ..MuteRules::default() }, &taxonomy).unwrap()
+        };
+        let rust = tagged("medium:1", &["rust"]);
+        let python = tagged("medium:2", &["python"]);
+
+        let alias = muting("RustLang");
+        assert_eq!(alias.matching_rule(&rust).as_deref(), Some("tag: RustLang"));
+        assert_eq!(alias.matching_rule(&python), None);
+
+        let parent = muting("programming");
+        assert!(parent.matching_rule(&rust).is_some());
+        assert!(parent.matching_rule(&python).is_some());
+    }
+
+    #[test]
+    fn bad_cookie_entries_only_drop_themselves() {
+        let rules = MuteRules::parse(
+            r#"{"keywords": ["crypto", 42, " crypto "], "patterns": ["(unclosed", "^Show HN"], "domains": "example.com", "authors": ["pg"]}"#,
+        );
+        assert_eq!(rules.keywords, ["crypto"]);
+        assert_eq!(rules.patterns, ["^Show HN"]);
+        assert!(rules.domains.is_empty());
+        assert_eq!(rules.authors, ["pg"]);
+        assert!(MuteFilter::new(&rules, &Taxonomy::default()).is_ok());
+
+        assert_eq!(MuteRules::parse("not json"), MuteRules::default());
+    }
+}
@@ -0,0 +1,154 @@
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+use serde::Serialize;
+
+// Items per page when the request does not say
+pub const DEFAULT_PER_PAGE: usize = 20;
+// Upper bound on `per_page`, so one request cannot render the whole store
+pub const MAX_PER_PAGE: usize = 100;
+// Page links shown on either side of the current page
+const PAGE_WINDOW: usize = 2;
+
+// `?page=&per_page=` on listing routes; both are optional and clamped to sane values
+#[derive(Debug, Clone, Copy, Default, FromForm)]
+pub struct PageParams {
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageLink {
+    pub number: usize,
+    pub url: String,
+}
+
+// Context for the `pagination` block of the listing templates
+#[derive(Debug, Clone, Serialize)]
+pub struct Pagination {
+    pub current_page: usize,
+    pub per_page: usize,
+    pub total_items: usize,
+    pub total_pages: usize,
+    pub has_previous: bool,
+    pub has_next: bool,
+    pub previous_page_url: Option<String>,
+    pub next_page_url: Option<String>,
+    pub pages: Vec<PageLink>,
+}
+
+impl Pagination {
+    // Paginate `total_items` items listed at `base`, e.g. "/posts" or "/search?q=rust"
+    pub fn new(params: PageParams, total_items: usize, base: &str) -> Self {
+        let per_page = params.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE);
+        let total_pages = total_items.div_ceil(per_page).max(1);
+        let current_page = params.page.unwrap_or(1).clamp(1, total_pages);
+
+        // Keep an explicit page size in links, but leave the default out of the URL
+        let separator = if base.contains('?') { '&' } else { '?' };
+        let url = |number: usize| match params.per_page {
+            Some(_) => format!("{}{}page={}&per_page={}", base, separator, number, per_page),
+            None => format!("{}{}page={}", base, separator, number),
+        };
+
+        let first = current_page.saturating_sub(PAGE_WINDOW).max(1);
+        let last = (current_page + PAGE_WINDOW).min(total_pages);
+        Pagination {
+            current_page,
+            per_page,
+            total_items,
+            total_pages,
+            has_previous: current_page > 1,
+            has_next: current_page < total_pages,
+            previous_page_url: (current_page > 1).then(|| url(current_page - 1)),
+            next_page_url: (current_page < total_pages).then(|| url(current_page + 1)),
+            pages: (first..=last).map(|number| PageLink { number, url: url(number) }).collect(),
+        }
+    }
+
+    // Index of the first item on the current page
+    pub fn offset(&self) -> usize {
+        (self.current_page - 1) * self.per_page
+    }
+
+    // The current page's share of a full listing
+    pub fn slice<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        items.iter().skip(self.offset()).take(self.per_page).cloned().collect()
+    }
+
+    // Value for the `Link` header, e.g. `</posts?page=3>; rel="next"`
+    fn link_header(&self) -> Option<String> {
+        let links: Vec<String> = [(&self.previous_page_url, "prev"), (&self.next_page_url, "next")]
+            .into_iter()
+            .filter_map(|(url, rel)| url.as_ref().map(|url| format!("<{}>; rel=\"{}\"", url, rel)))
+            .collect();
+        (!links.is_empty()).then(|| links.join(", "))
+    }
+}
+
+// A paginated response: the page itself plus `Link` headers pointing at its neighbours
+pub struct Paginated<R> {
+    pub inner: R,
+    pub pagination: Pagination,
+}
+
+impl<'r, R: Responder<'r, 'static>> Responder<'r, 'static> for Paginated<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = self.inner.respond_to(request)?;
+        if let Some(link) = self.pagination.link_header() {
+            response.set_raw_header("Link", link);
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(page: Option<usize>, per_page: Option<usize>) -> PageParams {
+        PageParams { page, per_page }
+    }
+
+    #[test]
+    fn huge_page_is_clamped_to_the_last_page() {
+        let pagination = Pagination::new(params(Some(100_000_000_000), Some(100)), 250, "/search?q=a");
+        assert_eq!(pagination.current_page, 3);
+        assert_eq!(pagination.offset(), 200);
+        assert!(!pagination.has_next);
+        assert_eq!(pagination.previous_page_url.as_deref(), Some("/search?q=a&page=2&per_page=100"));
+    }
+
+    #[test]
+    fn page_size_is_clamped() {
+        assert_eq!(Pagination::new(params(None, Some(0)), 10, "/posts").per_page, 1);
+        assert_eq!(Pagination::new(params(None, Some(usize::MAX)), 10, "/posts").per_page, MAX_PER_PAGE);
+        assert_eq!(Pagination::new(params(None, None), 10, "/posts").per_page, DEFAULT_PER_PAGE);
+    }
+
+    #[test]
+    fn empty_listing_has_one_page() {
+        let pagination = Pagination::new(params(Some(0), None), 0, "/posts");
+        assert_eq!(pagination.current_page, 1);
+        assert_eq!(pagination.total_pages, 1);
+        assert_eq!(pagination.offset(), 0);
+        assert!(!pagination.has_previous && !pagination.has_next);
+        assert_eq!(pagination.pages.len(), 1);
+    }
+
+    #[test]
+    fn links_keep_an_explicit_page_size_only() {
+        let pagination = Pagination::new(params(Some(2), None), 100, "/posts");
+        assert_eq!(pagination.next_page_url.as_deref(), Some("/posts?page=3"));
+        let pagination = Pagination::new(params(Some(2), Some(10)), 100, "/posts?sort=title");
+        assert_eq!(pagination.next_page_url.as_deref(), Some("/posts?sort=title&page=3&per_page=10"));
+        let numbers: Vec<usize> = pagination.pages.iter().map(|page| page.number).collect();
+        assert_eq!(numbers, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn slice_returns_the_current_page() {
+        let items: Vec<usize> = (0..25).collect();
+        let pagination = Pagination::new(params(Some(3), Some(10)), items.len(), "/posts");
+        assert_eq!(pagination.slice(&items), (20..25).collect::<Vec<_>>());
+    }
+}
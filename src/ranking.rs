@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::engagement::Engagement;
+use crate::BlogArticle;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RankingConfig {
+    // Hours after which the recency signal has halved
+    pub half_life_hours: f64,
+    pub recency_weight: f64,
+    pub popularity_weight: f64,
+    pub mentions_weight: f64,
+    pub engagement_weight: f64,
+    // Multiplier per source name, e.g. "Hacker News" = 1.2; unlisted sources count 1.0
+    pub source_weights: HashMap<String, f64>,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        RankingConfig {
+            half_life_hours: 12.0,
+            recency_weight: 3.0,
+            popularity_weight: 2.0,
+            mentions_weight: 1.0,
+            engagement_weight: 1.0,
+            source_weights: HashMap::new(),
+        }
+    }
+}
+
+// 0 for nothing, 1 for the largest value seen, logarithmic in between
+fn log_scaled(value: u64, max: u64) -> f64 {
+    if max == 0 {
+        return 0.0;
+    }
+    (value as f64).ln_1p() / (max as f64).ln_1p()
+}
+
+// Upstream interest as a single number: points or reactions plus comments
+pub fn popularity(article: &BlogArticle) -> u64 {
+    article.score.unwrap_or(0).max(0) as u64 + article.comment_count.unwrap_or(0)
+}
+
+// Front page score of every article, keyed by article ID. Each signal lies in 0..=1
+// before weighting, so the configured weights are directly comparable.
+pub fn scores(
+    articles: &[BlogArticle],
+    config: &RankingConfig,
+    engagement: &Engagement,
+    now: DateTime<Utc>,
+) -> HashMap<String, f64> {
+    // HN points and Dev.to reactions live on different scales, so popularity is
+    // measured against the most popular article of the same source
+    let mut max_popularity: HashMap<&str, u64> = HashMap::new();
+    for article in articles {
+        let max = max_popularity.entry(&article.source).or_default();
+        *max = (*max).max(popularity(article));
+    }
+    let max_views = articles.iter().map(|article| engagement.views(&article.id)).max().unwrap_or(0);
+
+    articles
+        .iter()
+        .map(|article| {
+            let age_hours = (now - article.published_at.unwrap_or(article.fetched_at)).num_minutes().max(0) as f64 / 60.0;
+            let recency = 0.5f64.powf(age_hours / config.half_life_hours.max(0.1));
+            let popularity = log_scaled(popularity(article), max_popularity[article.source.as_str()]);
+            // 0 for one source, approaching 1 as more sources carry the story
+            let mentions = 1.0 - 1.0 / article.sources.len().max(1) as f64;
+            let engagement = log_scaled(engagement.views(&article.id), max_views);
+
+            // A story carried by several sources gets the best of their weights, unlisted ones included
+            let source_weight = article
+                .sources
+                .iter()
+                .map(|source| config.source_weights.get(source).copied().unwrap_or(1.0))
+                .reduce(f64::max)
+                .unwrap_or(1.0);
+
+            let score = source_weight
+                * (config.recency_weight * recency
+                    + config.popularity_weight * popularity
+                    + config.mentions_weight * mentions
+                    + config.engagement_weight * engagement);
+            (article.id.clone(), score)
+        })
+        .collect()
+}
+
+// Sort articles best first, falling back to the store's newest-first order on ties
+pub fn rank(articles: &mut [BlogArticle], config: &RankingConfig, engagement: &Engagement, now: DateTime<Utc>) {
+    let scores = scores(articles, config, engagement, now);
+    articles.sort_by(|a, b| scores[&b.id].total_cmp(&scores[&a.id]));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::Duration;
+
+    use super::*;
+    use crate::test_article;
+
+    fn only(recency: f64, popularity: f64, mentions: f64, engagement: f64) -> RankingConfig {
+        RankingConfig {
+            recency_weight: recency,
+            popularity_weight: popularity,
+            mentions_weight: mentions,
+            engagement_weight: engagement,
+            ..RankingConfig::default()
+        }
+    }
+
+    fn engagement() -> Engagement {
+        Engagement::open(PathBuf::from("/nonexistent/engagement.json"))
+    }
+
+    #[test]
+    fn recency_halves_every_half_life() {
+        let now = Utc::now();
+        let mut fresh = test_article("hacker-news:1", "Fresh");
+        fresh.published_at = Some(now);
+        let mut old = test_article("hacker-news:2", "Old");
+        old.published_at = Some(now - Duration::hours(12));
+
+        let scores = scores(&[fresh, old], &only(3.0, 0.0, 0.0, 0.0), &engagement(), now);
+        assert!((scores["hacker-news:1"] - 3.0).abs() < 1e-9);
+        assert!((scores["hacker-news:2"] - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn popularity_is_relative_to_the_same_source() {
+        let now = Utc::now();
+        let mut top = test_article("hacker-news:1", "Top");
+        top.score = Some(500);
+        top.comment_count = Some(120);
+        let mut modest = test_article("hacker-news:2", "Modest");
+        modest.score = Some(20);
+        let mut devto = test_article("dev-to:1", "Small but best on its source");
+        devto.score = Some(15);
+
+        let scores = scores(&[top, modest, devto], &only(0.0, 2.0, 0.0, 0.0), &engagement(), now);
+        assert!((scores["hacker-news:1"] - 2.0).abs() < 1e-9);
+        assert!((scores["dev-to:1"] - 2.0).abs() < 1e-9);
+        assert!(scores["hacker-news:2"] > 0.0 && scores["hacker-news:2"] < 2.0);
+    }
+
+    #[test]
+    fn mentions_views_and_source_weights_count() {
+        let now = Utc::now();
+        let engagement = engagement();
+        let mut shared = test_article("hacker-news:1", "Carried by two sources");
+        shared.sources.push("reddit".to_string());
+        let single = test_article("hacker-news:2", "Only on one source");
+        engagement.record_view("hacker-news:2");
+
+        let mut config = only(0.0, 0.0, 1.0, 1.0);
+        config.source_weights = HashMap::from([("hacker-news".to_string(), 0.5)]);
+        let scores = scores(&[shared, single], &config, &engagement, now);
+        // The unlisted "reddit" counts 1.0, which beats hacker-news' 0.5
+        assert!((scores["hacker-news:1"] - 0.5).abs() < 1e-9);
+        assert!((scores["hacker-news:2"] - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rank_sorts_best_first() {
+        let now = Utc::now();
+        let mut old = test_article("hacker-news:1", "Old");
+        old.published_at = Some(now - Duration::days(2));
+        let mut new = test_article("hacker-news:2", "New");
+        new.published_at = Some(now);
+        let mut articles = vec![old, new];
+
+        rank(&mut articles, &only(1.0, 0.0, 0.0, 0.0), &engagement(), now);
+        assert_eq!(articles[0].id, "hacker-news:2");
+    }
+}
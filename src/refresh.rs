@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::Utc;
+use rocket::fairing::AdHoc;
+
+use crate::alerts::{self, AlertStore, ChannelConfig};
+use crate::categorize::{self, CategoryEngine};
+use crate::classifier::Classifier;
+use crate::config::AppConfig;
+use crate::engagement::Engagement;
+use crate::keywords::KeywordExtractor;
+use crate::search::SearchIndex;
+use crate::similarity::Similarity;
+use crate::sources::SourceStore;
+use crate::store::ArticleStore;
+use crate::suggest::Suggester;
+use crate::taxonomy::Taxonomy;
+use crate::{fetch_blog_data, BlogArticle};
+
+// Everything a refresh cycle needs, cloned out of Rocket's managed state
+struct Pipeline {
+    store: ArticleStore,
+    engine: CategoryEngine,
+    classifier: Classifier,
+    taxonomy: Taxonomy,
+    keywords: KeywordExtractor,
+    search: SearchIndex,
+    suggester: Suggester,
+    similarity: Similarity,
+    engagement: Engagement,
+    sources: SourceStore,
+    alerts: AlertStore,
+    channels: Vec<ChannelConfig>,
+    public_url: String,
+    interval: Duration,
+    retention: chrono::Duration,
+}
+
+impl Pipeline {
+    // Fetch every source, store the result, then normalize tags and categorize
+    // the whole store so config changes also reach articles from earlier runs
+    async fn run_once(&self) {
+        let articles = fetch_blog_data(&self.sources.feeds(), &self.store).await;
+        let indexed: HashMap<String, u64> =
+            self.store.all().iter().map(|article| (article.id.clone(), SearchIndex::fingerprint(article))).collect();
+        let new_ids = self.store.upsert(articles);
+        let expired = self.store.prune(Utc::now() - self.retention);
+        self.search.remove_articles(&expired);
+        self.engagement.forget(&expired);
+        self.store.update_all(|article| {
+            article.tags = self.taxonomy.normalize(&article.tags);
+            categorize::categorize(&self.engine, &self.classifier, article);
+        });
+
+        // Keyword scores depend on the whole corpus, so recompute them for every article
+        let mut keywords = self.keywords.extract(&self.store.all());
+        self.store.update_all(|article| {
+            article.keywords = keywords.remove(&article.id).unwrap_or_default();
+        });
+
+        // Reindex what this run added or changed: fetched articles, and earlier ones whose
+        // categories or tags moved with the configuration
+        let stored = self.store.all();
+        let changed: Vec<BlogArticle> = stored
+            .iter()
+            .filter(|article| indexed.get(&article.id) != Some(&SearchIndex::fingerprint(article)))
+            .cloned()
+            .collect();
+        self.search.index_articles(&changed);
+        self.suggester.rebuild(&stored);
+        self.similarity.rebuild(&stored);
+        self.store.save();
+        self.engagement.save();
+        println!("Refresh stored {} new articles and dropped {} expired ones", new_ids.len(), expired.len());
+
+        // Saved searches only alert on articles this run stored for the first time
+        let raised = self.alerts.evaluate(&new_ids, &self.search, &self.store);
+        if !raised.is_empty() {
+            self.alerts.save();
+        }
+        for (search, matches) in &raised {
+            alerts::deliver(&self.channels, search, matches, &self.public_url).await;
+        }
+    }
+
+    async fn run_forever(self) {
+        loop {
+            self.run_once().await;
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+}
+
+// Start the background refresh loop once the server is up
+pub fn fairing() -> AdHoc {
+    AdHoc::on_liftoff("Refresh pipeline", |rocket| {
+        Box::pin(async move {
+            let config = rocket.state::<AppConfig>().expect("AppConfig is managed");
+            let pipeline = Pipeline {
+                store: rocket.state::<ArticleStore>().expect("ArticleStore is managed").clone(),
+                engine: rocket.state::<CategoryEngine>().expect("CategoryEngine is managed").clone(),
+                classifier: rocket.state::<Classifier>().expect("Classifier is managed").clone(),
+                taxonomy: rocket.state::<Taxonomy>().expect("Taxonomy is managed").clone(),
+                keywords: KeywordExtractor::new(&config.keywords),
+                search: rocket.state::<SearchIndex>().expect("SearchIndex is managed").clone(),
+                suggester: rocket.state::<Suggester>().expect("Suggester is managed").clone(),
+                similarity: rocket.state::<Similarity>().expect("Similarity is managed").clone(),
+                engagement: rocket.state::<Engagement>().expect("Engagement is managed").clone(),
+                sources: rocket.state::<SourceStore>().expect("SourceStore is managed").clone(),
+                alerts: rocket.state::<AlertStore>().expect("AlertStore is managed").clone(),
+                channels: config.alerts.channels.clone(),
+                public_url: config.public_url.clone(),
+                interval: Duration::from_secs(config.refresh_interval.max(60)),
+                retention: chrono::Duration::days(config.retention_days.max(1) as i64),
+            };
+            tokio::spawn(pipeline.run_forever());
+        })
+    })
+}
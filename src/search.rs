@@ -0,0 +1,595 @@
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Bound;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use tantivy::collector::{Count, DocSetCollector, TopDocs};
+use tantivy::query::{AllQuery, BooleanQuery, Occur, Query, QueryParser, RangeQuery, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, Schema, Value, FAST, INDEXED, STORED, STRING, TEXT};
+use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, SnippetGenerator, TantivyDocument, Term};
+
+use crate::ids::{slugify, stable_hash};
+use crate::store::ArticleStore;
+use crate::text::strip_html;
+use crate::BlogArticle;
+
+// Memory budget of the index writer
+const WRITER_MEMORY: usize = 50_000_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Fields {
+    id: Field,
+    title: Field,
+    excerpt: Field,
+    content: Field,
+    tags: Field,
+    source: Field,
+    author: Field,
+    // Exact-match slugs behind the `source:`, `category:` and `tag:` qualifiers
+    source_slug: Field,
+    category: Field,
+    tag: Field,
+    published: Field,
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let fields = Fields {
+        id: builder.add_text_field("id", STRING | STORED),
+        title: builder.add_text_field("title", TEXT),
+        excerpt: builder.add_text_field("excerpt", TEXT),
+        content: builder.add_text_field("content", TEXT),
+        tags: builder.add_text_field("tags", TEXT),
+        source: builder.add_text_field("source", TEXT),
+        author: builder.add_text_field("author", TEXT),
+        source_slug: builder.add_text_field("source_slug", STRING),
+        category: builder.add_text_field("category", STRING),
+        tag: builder.add_text_field("tag", STRING),
+        published: builder.add_date_field("published", INDEXED | FAST),
+    };
+    (builder.build(), fields)
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub source: String,
+    pub author: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub score: f32,
+    // HTML with matched terms wrapped in <b>; everything else is escaped
+    pub title_html: String,
+    pub snippet_html: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: usize,
+    // Qualifier that narrows a search to this value, e.g. `source:hacker-news`
+    pub filter: String,
+}
+
+// Counts over every matching article, not only the current page of hits
+#[derive(Debug, Default, Serialize)]
+pub struct Facets {
+    pub sources: Vec<FacetCount>,
+    pub categories: Vec<FacetCount>,
+    pub dates: Vec<FacetCount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResults {
+    pub query: String,
+    pub total: usize,
+    pub hits: Vec<SearchHit>,
+    pub facets: Facets,
+}
+
+// One piece of a search query, e.g. `-source:medium` or `"rust async"`
+#[derive(Debug, PartialEq)]
+enum Clause {
+    Text(String),
+    Source(String),
+    Category(String),
+    Tag(String),
+    Author(String),
+    Before(NaiveDate),
+    After(NaiveDate),
+}
+
+// Split a query into whitespace-separated clauses, keeping quoted phrases together.
+// Each clause is paired with whether it was negated with a leading `-`.
+fn parse_clauses(query: &str) -> Vec<(bool, Clause)> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+        .into_iter()
+        .filter_map(|token| {
+            let (negated, token) = match token.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() => (true, rest.to_string()),
+                _ => (false, token),
+            };
+            let clause = match token.split_once(':') {
+                Some((field, value)) => {
+                    let value = value.trim_matches('"').to_string();
+                    match field.to_lowercase().as_str() {
+                        "source" => Clause::Source(value),
+                        "category" => Clause::Category(value),
+                        "tag" => Clause::Tag(value),
+                        "author" => Clause::Author(value),
+                        // An unparseable date is ignored rather than matching nothing
+                        "before" => Clause::Before(NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok()?),
+                        "after" => Clause::After(NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok()?),
+                        _ => Clause::Text(token),
+                    }
+                }
+                None => Clause::Text(token),
+            };
+            Some((negated, clause))
+        })
+        .collect()
+}
+
+fn date_bound(date: NaiveDate) -> tantivy::DateTime {
+    tantivy::DateTime::from_timestamp_secs(date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp())
+}
+
+// Source, category and recency counts for a set of articles
+pub fn facets(articles: &[BlogArticle], now: DateTime<Utc>) -> Facets {
+    let mut sources: HashMap<&str, usize> = HashMap::new();
+    let mut categories: HashMap<&str, usize> = HashMap::new();
+    for article in articles {
+        for source in &article.sources {
+            *sources.entry(source).or_default() += 1;
+        }
+        for category in &article.categories {
+            *categories.entry(category).or_default() += 1;
+        }
+    }
+
+    let counted = |counts: HashMap<&str, usize>, qualifier: &str| {
+        let mut counted: Vec<FacetCount> = counts
+            .into_iter()
+            .map(|(value, count)| FacetCount {
+                value: value.to_string(),
+                count,
+                filter: format!("{}:{}", qualifier, slugify(value)),
+            })
+            .collect();
+        counted.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        counted
+    };
+
+    // Buckets are cumulative so each one maps onto a single `after:` qualifier
+    let today = now.date_naive();
+    let dates = [("Today", 0), ("Past week", 7), ("Past month", 30)]
+        .into_iter()
+        .map(|(label, days)| {
+            let since = today - Duration::days(days);
+            FacetCount {
+                value: label.to_string(),
+                count: articles
+                    .iter()
+                    .filter(|article| article.published_at.unwrap_or(article.fetched_at).date_naive() >= since)
+                    .count(),
+                filter: format!("after:{}", since.format("%Y-%m-%d")),
+            }
+        })
+        .filter(|facet| facet.count > 0)
+        .collect();
+
+    Facets {
+        sources: counted(sources, "source"),
+        categories: counted(categories, "category"),
+        dates,
+    }
+}
+
+// Full-text index over stored articles, kept on disk next to the article store.
+// Cloning is cheap: clones share the same index, reader and writer.
+#[derive(Clone)]
+pub struct SearchIndex {
+    index: Index,
+    reader: IndexReader,
+    writer: Arc<Mutex<IndexWriter>>,
+    fields: Fields,
+}
+
+fn open_index(dir: &Path, schema: Schema) -> tantivy::Result<Index> {
+    fs::create_dir_all(dir)?;
+    let directory = tantivy::directory::MmapDirectory::open(dir)?;
+    Index::open_or_create(directory, schema)
+}
+
+impl SearchIndex {
+    // Open the index in `dir`, rebuilding it from the store when it is new or its schema changed
+    pub fn open(dir: &Path, store: &ArticleStore) -> tantivy::Result<Self> {
+        let (schema, fields) = schema();
+        let index = match open_index(dir, schema.clone()) {
+            Ok(index) => index,
+            Err(e) => {
+                eprintln!("Rebuilding search index {}: {}", dir.display(), e);
+                let _ = fs::remove_dir_all(dir);
+                open_index(dir, schema)?
+            }
+        };
+
+        let reader = index.reader_builder().reload_policy(ReloadPolicy::Manual).try_into()?;
+        let writer = index.writer(WRITER_MEMORY)?;
+        let search = SearchIndex {
+            index,
+            reader,
+            writer: Arc::new(Mutex::new(writer)),
+            fields,
+        };
+
+        if search.reader.searcher().num_docs() == 0 {
+            search.index_articles(&store.all());
+        }
+        Ok(search)
+    }
+
+    // Hash of everything the index stores about an article; when it changes, so must the index
+    pub fn fingerprint(article: &BlogArticle) -> u64 {
+        stable_hash(
+            &[
+                article.title.as_str(),
+                &article.excerpt,
+                &article.content,
+                &article.tags.join(" "),
+                &article.sources.join(" "),
+                &article.categories.join(" "),
+                article.author.as_deref().unwrap_or(""),
+                &article.published_at.unwrap_or(article.fetched_at).timestamp().to_string(),
+            ]
+            .join("\n"),
+        )
+    }
+
+    fn document(&self, article: &BlogArticle) -> TantivyDocument {
+        let f = self.fields;
+        let seen_at = article.published_at.unwrap_or(article.fetched_at);
+        let mut document = doc!(
+            f.id => article.id.clone(),
+            f.title => article.title.clone(),
+            f.excerpt => article.excerpt.clone(),
+            f.content => strip_html(&article.content),
+            f.tags => article.tags.join(" "),
+            f.source => article.sources.join(" "),
+            f.author => article.author.clone().unwrap_or_default(),
+            f.published => tantivy::DateTime::from_timestamp_secs(seen_at.timestamp()),
+        );
+        for source in &article.sources {
+            document.add_text(f.source_slug, slugify(source));
+        }
+        for category in &article.categories {
+            document.add_text(f.category, slugify(category));
+        }
+        for tag in &article.tags {
+            document.add_text(f.tag, slugify(tag));
+        }
+        document
+    }
+
+    // Add or replace the given articles and make them searchable
+    pub fn index_articles(&self, articles: &[BlogArticle]) {
+        if articles.is_empty() {
+            return;
+        }
+
+        let mut writer = self.writer.lock().unwrap();
+        for article in articles {
+            writer.delete_term(Term::from_field_text(self.fields.id, &article.id));
+            if let Err(e) = writer.add_document(self.document(article)) {
+                eprintln!("Failed to index article {}: {}", article.id, e);
+            }
+        }
+        self.commit(&mut writer);
+    }
+
+    // Take the given articles out of the index
+    pub fn remove_articles(&self, ids: &[String]) {
+        if ids.is_empty() {
+            return;
+        }
+
+        let mut writer = self.writer.lock().unwrap();
+        for id in ids {
+            writer.delete_term(Term::from_field_text(self.fields.id, id));
+        }
+        self.commit(&mut writer);
+    }
+
+    fn commit(&self, writer: &mut IndexWriter) {
+        if let Err(e) = writer.commit() {
+            eprintln!("Failed to commit search index: {}", e);
+            return;
+        }
+        if let Err(e) = self.reader.reload() {
+            eprintln!("Failed to reload search index: {}", e);
+        }
+    }
+
+    fn exact(&self, field: Field, value: &str) -> Box<dyn Query> {
+        Box::new(TermQuery::new(Term::from_field_text(field, &slugify(value)), IndexRecordOption::Basic))
+    }
+
+    // Turn the query syntax into a tantivy query. Free text is ranked across the text
+    // fields; qualifiers only filter. Malformed text degrades to the parts that parse.
+    // Articles in `excluded` never match, e.g. ones the reader muted.
+    fn build_query(&self, query: &str, excluded: &[String]) -> Box<dyn Query> {
+        let f = self.fields;
+        let mut text = QueryParser::for_index(&self.index, vec![f.title, f.excerpt, f.content, f.tags, f.source, f.author]);
+        text.set_field_boost(f.title, 3.0);
+        text.set_field_boost(f.tags, 2.0);
+        let author = QueryParser::for_index(&self.index, vec![f.author]);
+
+        let mut positive_text: Vec<String> = Vec::new();
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for (negated, clause) in parse_clauses(query) {
+            let occur = if negated { Occur::MustNot } else { Occur::Must };
+            let subquery: Box<dyn Query> = match clause {
+                Clause::Text(words) if !negated => {
+                    positive_text.push(words);
+                    continue;
+                }
+                Clause::Text(words) => text.parse_query_lenient(&words).0,
+                Clause::Source(value) => self.exact(f.source_slug, &value),
+                Clause::Category(value) => self.exact(f.category, &value),
+                Clause::Tag(value) => self.exact(f.tag, &value),
+                Clause::Author(value) => author.parse_query_lenient(&format!("\"{}\"", value.replace('"', ""))).0,
+                Clause::Before(date) => Box::new(RangeQuery::new_date_bounds(
+                    "published".to_string(),
+                    Bound::Unbounded,
+                    Bound::Excluded(date_bound(date)),
+                )),
+                Clause::After(date) => Box::new(RangeQuery::new_date_bounds(
+                    "published".to_string(),
+                    Bound::Included(date_bound(date)),
+                    Bound::Unbounded,
+                )),
+            };
+            clauses.push((occur, subquery));
+        }
+
+        for id in excluded {
+            let term = Term::from_field_text(f.id, id);
+            clauses.push((Occur::MustNot, Box::new(TermQuery::new(term, IndexRecordOption::Basic))));
+        }
+        if !positive_text.is_empty() {
+            clauses.push((Occur::Must, text.parse_query_lenient(&positive_text.join(" ")).0));
+        }
+        // Exclusions alone have nothing to exclude from
+        if !clauses.iter().any(|(occur, _)| *occur == Occur::Must) {
+            clauses.push((Occur::Must, Box::new(AllQuery)));
+        }
+        Box::new(BooleanQuery::new(clauses))
+    }
+
+    // Which of the candidate articles match a query, e.g. a saved search against new arrivals
+    pub fn matching(&self, query: &str, candidates: &[String]) -> Vec<String> {
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+        let f = self.fields;
+        let candidates: Vec<(Occur, Box<dyn Query>)> = candidates
+            .iter()
+            .map(|id| {
+                let term = Term::from_field_text(f.id, id);
+                (Occur::Should, Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>)
+            })
+            .collect();
+        let restricted = BooleanQuery::new(vec![
+            (Occur::Must, self.build_query(query, &[])),
+            (Occur::Must, Box::new(BooleanQuery::new(candidates))),
+        ]);
+
+        let searcher = self.reader.searcher();
+        let matched = match searcher.search(&restricted, &DocSetCollector) {
+            Ok(matched) => matched,
+            Err(e) => {
+                eprintln!("Search for {:?} failed: {}", query, e);
+                return Vec::new();
+            }
+        };
+        matched
+            .into_iter()
+            .filter_map(|address| {
+                let document: TantivyDocument = searcher.doc(address).ok()?;
+                Some(document.get_first(f.id)?.as_str()?.to_string())
+            })
+            .collect()
+    }
+
+    // Number of articles matching a query
+    pub fn count(&self, query: &str, excluded: &[String]) -> usize {
+        let parsed = self.build_query(query, excluded);
+        self.reader.searcher().search(&*parsed, &Count).unwrap_or_else(|e| {
+            eprintln!("Search for {:?} failed: {}", query, e);
+            0
+        })
+    }
+
+    // Ranked, highlighted matches for a query, with facet counts over all matches.
+    // The window is clamped to the matches, since tantivy allocates for `limit + offset` up front.
+    pub fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        offset: usize,
+        excluded: &[String],
+        store: &ArticleStore,
+    ) -> SearchResults {
+        let f = self.fields;
+        let parsed = self.build_query(query, excluded);
+
+        let searcher = self.reader.searcher();
+        let matches = searcher.search(&*parsed, &Count).unwrap_or(0);
+        let offset = offset.min(matches);
+        let limit = limit.min(matches - offset).max(1);
+        let collectors = (TopDocs::with_limit(limit).and_offset(offset), Count, DocSetCollector);
+        let (top_docs, total, matched) = match searcher.search(&*parsed, &collectors) {
+            Ok(results) => results,
+            Err(e) => {
+                eprintln!("Search for {:?} failed: {}", query, e);
+                return SearchResults {
+                    query: query.to_string(),
+                    total: 0,
+                    hits: Vec::new(),
+                    facets: Facets::default(),
+                };
+            }
+        };
+
+        let article_at = |address| {
+            let document: TantivyDocument = searcher.doc(address).ok()?;
+            let id = document.get_first(f.id)?.as_str()?.to_string();
+            store.get(&id)
+        };
+        let matched_articles: Vec<BlogArticle> = matched.into_iter().filter_map(article_at).collect();
+
+        let title_snippets = SnippetGenerator::create(&searcher, &*parsed, f.title).ok().map(|mut generator| {
+            generator.set_max_num_chars(300);
+            generator
+        });
+        let content_snippets = SnippetGenerator::create(&searcher, &*parsed, f.content).ok();
+
+        let hits = top_docs
+            .into_iter()
+            .filter_map(|(score, address)| {
+                let article = article_at(address)?;
+
+                let title_html = title_snippets
+                    .as_ref()
+                    .map(|generator| generator.snippet(&article.title))
+                    .filter(|snippet| !snippet.is_empty())
+                    .map(|snippet| snippet.to_html())
+                    .unwrap_or_else(|| tera::escape_html(&article.title));
+                let snippet_html = content_snippets
+                    .as_ref()
+                    .map(|generator| generator.snippet(&strip_html(&article.content)))
+                    .filter(|snippet| !snippet.is_empty())
+                    .map(|snippet| snippet.to_html())
+                    .unwrap_or_else(|| tera::escape_html(&article.excerpt));
+
+                Some(SearchHit {
+                    id: article.id,
+                    title: article.title,
+                    url: article.url,
+                    source: article.sources.join(", "),
+                    author: article.author,
+                    published_at: article.published_at,
+                    score,
+                    title_html,
+                    snippet_html,
+                })
+            })
+            .collect();
+
+        SearchResults {
+            query: query.to_string(),
+            total,
+            hits,
+            facets: facets(&matched_articles, Utc::now()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_article;
+
+    #[test]
+    fn huge_offset_is_clamped_to_the_matches() {
+        let dir = std::env::temp_dir().join(format!("blog-engine-search-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = ArticleStore::open(dir.join("articles.json"));
+        store.upsert(vec![
+            test_article("medium:1", "Rust async in practice"),
+            test_article("medium:2", "Rust 2.0 released"),
+            test_article("medium:3", "Gardening tips"),
+        ]);
+        let index = SearchIndex::open(&dir.join("index"), &store).unwrap();
+
+        assert_eq!(index.count("rust", &[]), 2);
+        let results = index.search("rust", 100, usize::MAX / 2, &[], &store);
+        assert_eq!(results.total, 2);
+        assert!(results.hits.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn quoted_phrases_stay_together() {
+        assert_eq!(
+            parse_clauses(r#""rust async"  compiler source:"hacker news""#),
+            vec![
+                (false, Clause::Text(r#""rust async""#.to_string())),
+                (false, Clause::Text("compiler".to_string())),
+                (false, Clause::Source("hacker news".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_leading_dash_negates_a_clause() {
+        assert_eq!(
+            parse_clauses("-source:medium -crypto after:2024-01-31 -"),
+            vec![
+                (true, Clause::Source("medium".to_string())),
+                (true, Clause::Text("crypto".to_string())),
+                (false, Clause::After(NaiveDate::from_ymd_opt(2024, 1, 31).unwrap())),
+                (false, Clause::Text("-".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_text_and_bad_dates_are_dropped() {
+        assert_eq!(
+            parse_clauses("lang:rust Tag:Security before:yesterday"),
+            vec![
+                (false, Clause::Text("lang:rust".to_string())),
+                (false, Clause::Tag("Security".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn fingerprint_follows_indexed_fields_only() {
+        let article = test_article("medium:1", "Rust 2.0 released");
+        let mut recategorized = article.clone();
+        recategorized.categories = vec!["tech".to_string()];
+        let mut rekeyworded = article.clone();
+        rekeyworded.keywords = vec!["rust".to_string()];
+
+        assert_ne!(SearchIndex::fingerprint(&article), SearchIndex::fingerprint(&recategorized));
+        assert_eq!(SearchIndex::fingerprint(&article), SearchIndex::fingerprint(&rekeyworded));
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::Serialize;
+
+use crate::store::ArticleStore;
+use crate::text::{strip_html, tokenize};
+use crate::BlogArticle;
+
+// Related articles kept per article
+const RELATED_LIMIT: usize = 5;
+// Below this cosine similarity articles only share incidental words
+const MIN_SIMILARITY: f64 = 0.1;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedArticle {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub source: String,
+    pub similarity: f64,
+}
+
+// Unit-length TF-IDF weights by term
+type TermVector = HashMap<String, f64>;
+
+// "More like this" over the stored corpus. Term vectors are rebuilt on every refresh;
+// each article's related list is computed on first request and cached until then.
+// Cloning is cheap: clones share the same vectors and cache.
+#[derive(Debug, Clone, Default)]
+pub struct Similarity {
+    vectors: Arc<RwLock<HashMap<String, TermVector>>>,
+    cache: Arc<Mutex<HashMap<String, Vec<RelatedArticle>>>>,
+}
+
+// Raw term counts; title and tags say more about the topic than body text, so they count double
+fn term_counts(article: &BlogArticle) -> HashMap<String, f64> {
+    let mut counts: HashMap<String, f64> = HashMap::new();
+    let weighted = [
+        (article.title.clone(), 2.0),
+        (article.tags.join(" "), 2.0),
+        (article.excerpt.clone(), 1.0),
+        (strip_html(&article.content), 1.0),
+    ];
+    for (text, weight) in weighted {
+        for term in tokenize(&text) {
+            *counts.entry(term).or_default() += weight;
+        }
+    }
+    counts
+}
+
+fn cosine(a: &TermVector, b: &TermVector) -> f64 {
+    let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    small.iter().filter_map(|(term, weight)| large.get(term).map(|other| weight * other)).sum()
+}
+
+impl Similarity {
+    pub fn new(articles: &[BlogArticle]) -> Self {
+        let similarity = Similarity::default();
+        similarity.rebuild(articles);
+        similarity
+    }
+
+    // Recompute every term vector and forget cached related lists
+    pub fn rebuild(&self, articles: &[BlogArticle]) {
+        let counts: Vec<HashMap<String, f64>> = articles.iter().map(term_counts).collect();
+        let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+        for terms in &counts {
+            for term in terms.keys() {
+                *document_frequency.entry(term.as_str()).or_default() += 1;
+            }
+        }
+
+        let documents = articles.len() as f64;
+        let vectors: HashMap<String, TermVector> = articles
+            .iter()
+            .zip(&counts)
+            .map(|(article, terms)| {
+                let mut vector: TermVector = terms
+                    .iter()
+                    .map(|(term, &count)| {
+                        let idf = ((documents + 1.0) / (document_frequency[term.as_str()] as f64 + 1.0)).ln() + 1.0;
+                        (term.clone(), (1.0 + count.ln()) * idf)
+                    })
+                    .collect();
+                let norm = vector.values().map(|weight| weight * weight).sum::<f64>().sqrt();
+                if norm > 0.0 {
+                    vector.values_mut().for_each(|weight| *weight /= norm);
+                }
+                (article.id.clone(), vector)
+            })
+            .collect();
+
+        *self.vectors.write().unwrap() = vectors;
+        self.cache.lock().unwrap().clear();
+    }
+
+    // Most similar articles from other sources, or None when the article is unknown
+    pub fn related(&self, id: &str, store: &ArticleStore) -> Option<Vec<RelatedArticle>> {
+        if let Some(related) = self.cache.lock().unwrap().get(id) {
+            return Some(related.clone());
+        }
+
+        let article = store.get(id)?;
+        let vectors = self.vectors.read().unwrap();
+        // Articles stored since the last rebuild have no vector yet
+        let Some(vector) = vectors.get(id) else {
+            return Some(Vec::new());
+        };
+
+        let mut scored: Vec<(f64, &String)> = vectors
+            .iter()
+            .filter(|(other, _)| other.as_str() != id)
+            .map(|(other, other_vector)| (cosine(vector, other_vector), other))
+            .filter(|(similarity, _)| *similarity >= MIN_SIMILARITY)
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+        let related: Vec<RelatedArticle> = scored
+            .into_iter()
+            .filter_map(|(similarity, other)| {
+                let other = store.get(other)?;
+                // The point is another outlet's take, not more from the same feed
+                let shares_source = other.sources.iter().any(|source| article.sources.contains(source));
+                (!shares_source).then_some(RelatedArticle {
+                    id: other.id,
+                    title: other.title,
+                    url: other.url,
+                    source: other.source,
+                    similarity,
+                })
+            })
+            .take(RELATED_LIMIT)
+            .collect();
+
+        self.cache.lock().unwrap().insert(id.to_string(), related.clone());
+        Some(related)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::test_article;
+
+    fn corpus() -> Vec<BlogArticle> {
+        vec![
+            test_article("hacker-news:1", "Rust async closures land in stable compiler"),
+            test_article("reddit:1", "Async closures land in stable Rust"),
+            test_article("hacker-news:2", "Rust compiler adds async closures"),
+            test_article("lobsters:1", "Rust compiler performance report"),
+            test_article("medium:1", "Sourdough bread baking at home"),
+        ]
+    }
+
+    #[test]
+    fn vectors_are_unit_length_so_cosine_is_bounded() {
+        let similarity = Similarity::new(&corpus());
+        let vectors = similarity.vectors.read().unwrap();
+        let rust = &vectors["hacker-news:1"];
+        assert!((cosine(rust, rust) - 1.0).abs() < 1e-9);
+        assert!(cosine(rust, &vectors["reddit:1"]) > cosine(rust, &vectors["lobsters:1"]));
+        assert_eq!(cosine(rust, &vectors["medium:1"]), 0.0);
+    }
+
+    #[test]
+    fn related_articles_come_from_other_sources_most_similar_first() {
+        let store = ArticleStore::open(PathBuf::from("/nonexistent/articles.json"));
+        store.upsert(corpus());
+        let similarity = Similarity::new(&store.all());
+
+        let related = similarity.related("hacker-news:1", &store).unwrap();
+        let ids: Vec<&str> = related.iter().map(|article| article.id.as_str()).collect();
+        assert_eq!(ids, ["reddit:1", "lobsters:1"]);
+        assert!(related[0].similarity > related[1].similarity);
+        assert!(similarity.related("medium:1", &store).unwrap().is_empty());
+        assert!(similarity.related("medium:404", &store).is_none());
+    }
+}
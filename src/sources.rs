@@ -0,0 +1,525 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use reqwest::Client;
+use rocket::http::Status;
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest, Request};
+use rocket::State;
+use serde::{Deserialize, Serialize};
+
+use crate::admin;
+use crate::feeds::escape_xml;
+use crate::json_file::JsonFile;
+use crate::text::strip_html;
+use crate::{ids, parse_timestamp, BlogArticle};
+
+// Items taken from each feed per refresh
+const ITEMS_PER_FEED: usize = 20;
+
+// Characters of the feed summary kept as an article's excerpt
+const EXCERPT_LENGTH: usize = 300;
+
+// Feeds larger than this are dropped rather than read into memory
+const MAX_FEED_BYTES: usize = 5 * 1024 * 1024;
+
+// An RSS or Atom feed fetched alongside the built-in scrapers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedSource {
+    pub name: String,
+    // The feed document itself
+    pub url: String,
+    // The site the feed belongs to, if known
+    #[serde(default)]
+    pub site_url: Option<String>,
+    // Outermost first, e.g. ["Tech", "Rust"]; added to every article as tags, and the
+    // outermost one is the articles' category unless a reader picks another
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SourcesConfig {
+    // Feeds to fetch in addition to those imported at runtime
+    pub feeds: Vec<FeedSource>,
+    // Bearer token required by POST /sources/import; importing is disabled without one
+    pub import_token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportSummary {
+    pub imported: Vec<FeedSource>,
+    // Outlines whose feed URL was already a source
+    pub skipped: usize,
+}
+
+// Feed-backed sources: the configured ones plus any imported from OPML, persisted as JSON.
+// Cloning is cheap: clones share the same list.
+#[derive(Clone)]
+pub struct SourceStore {
+    feeds: Arc<RwLock<Vec<FeedSource>>>,
+    file: JsonFile<Vec<FeedSource>>,
+    import_token: Option<String>,
+}
+
+impl SourceStore {
+    // Load imported sources from disk and add configured ones that are not there yet
+    pub fn open(path: PathBuf, config: &SourcesConfig) -> Self {
+        let file = JsonFile::new(path, "sources").pretty();
+        let mut feeds: Vec<FeedSource> = file.load().unwrap_or_default();
+        for feed in &config.feeds {
+            if !feeds.iter().any(|existing| existing.url == feed.url) {
+                feeds.push(feed.clone());
+            }
+        }
+
+        SourceStore {
+            feeds: Arc::new(RwLock::new(feeds)),
+            file,
+            import_token: config.import_token.clone().filter(|token| !token.is_empty()),
+        }
+    }
+
+    pub fn feeds(&self) -> Vec<FeedSource> {
+        self.feeds.read().unwrap().clone()
+    }
+
+    // Add sources whose feed URL is new; the next refresh starts fetching them
+    pub fn import(&self, sources: Vec<FeedSource>) -> ImportSummary {
+        let mut feeds = self.feeds.write().unwrap();
+        let mut summary = ImportSummary { imported: Vec::new(), skipped: 0 };
+        for source in sources {
+            if feeds.iter().any(|existing| existing.url == source.url) {
+                summary.skipped += 1;
+            } else {
+                feeds.push(source.clone());
+                summary.imported.push(source);
+            }
+        }
+        drop(feeds);
+        if !summary.imported.is_empty() {
+            self.save();
+        }
+        summary
+    }
+
+    // Write the sources to disk, replacing the previous file atomically
+    pub fn save(&self) {
+        self.file.save(&self.feeds.read().unwrap());
+    }
+}
+
+// Guard for routes that add sources: the server fetches whatever URLs they name, so only a
+// request carrying the configured import token gets through
+pub struct SourceAdmin;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SourceAdmin {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, &'static str> {
+        let expected = match request.guard::<&State<SourceStore>>().await {
+            Outcome::Success(sources) => sources.import_token.clone(),
+            _ => None,
+        };
+        let Some(expected) = expected else {
+            return Outcome::Error((Status::Forbidden, "Importing sources is disabled"));
+        };
+        if admin::bearer_matches(request, &expected) {
+            Outcome::Success(SourceAdmin)
+        } else {
+            Outcome::Error((Status::Unauthorized, "Missing or wrong import token"))
+        }
+    }
+}
+
+// Attributes of an element by local name, unescaped
+fn attributes(element: &BytesStart<'_>) -> HashMap<String, String> {
+    element
+        .attributes()
+        .flatten()
+        .filter_map(|attribute| {
+            let name = String::from_utf8_lossy(attribute.key.local_name().as_ref()).to_string();
+            attribute.unescape_value().ok().map(|value| (name, value.trim().to_string()))
+        })
+        .collect()
+}
+
+// Feed sources from an OPML document. Every outline with an `xmlUrl` is a feed; the outlines
+// it is nested in become its categories, followed by any in its own `category` attribute.
+pub fn parse_opml(xml: &str) -> Result<Vec<FeedSource>, String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut sources = Vec::new();
+    let mut is_opml = false;
+    // One entry per open <outline>: the folder title, or None for a feed with children
+    let mut folders: Vec<Option<String>> = Vec::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid OPML: {}", e))?;
+        let (element, has_children) = match &event {
+            Event::Start(element) => (element, true),
+            Event::Empty(element) => (element, false),
+            Event::End(element) => {
+                if element.local_name().as_ref() == b"outline" {
+                    folders.pop();
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        match element.local_name().as_ref() {
+            b"opml" => is_opml = true,
+            b"outline" => {
+                let attributes = attributes(element);
+                let title = attributes
+                    .get("text")
+                    .or_else(|| attributes.get("title"))
+                    .filter(|title| !title.is_empty())
+                    .cloned();
+                match attributes.get("xmlUrl").filter(|url| !url.is_empty()) {
+                    Some(url) => {
+                        let mut categories: Vec<String> = folders.iter().flatten().cloned().collect();
+                        // The OPML category attribute holds comma-separated, slash-delimited paths
+                        for path in attributes.get("category").map(String::as_str).unwrap_or("").split(',') {
+                            for category in path.split('/').map(str::trim).filter(|c| !c.is_empty()) {
+                                if !categories.iter().any(|existing| existing == category) {
+                                    categories.push(category.to_string());
+                                }
+                            }
+                        }
+                        sources.push(FeedSource {
+                            name: title.unwrap_or_else(|| url.clone()),
+                            url: url.clone(),
+                            site_url: attributes.get("htmlUrl").filter(|url| !url.is_empty()).cloned(),
+                            categories,
+                        });
+                        if has_children {
+                            folders.push(None);
+                        }
+                    }
+                    None if has_children => folders.push(title),
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !is_opml {
+        return Err("Not an OPML document".to_string());
+    }
+    Ok(sources)
+}
+
+// An OPML 2.0 document listing the sources, nested by their categories
+pub fn opml(sources: &[FeedSource], title: &str) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<opml version=\"2.0\">\n");
+    xml.push_str("  <head>\n");
+    xml.push_str(&format!("    <title>{}</title>\n", escape_xml(title)));
+    xml.push_str(&format!("    <dateCreated>{}</dateCreated>\n", Utc::now().to_rfc2822()));
+    xml.push_str("  </head>\n");
+    xml.push_str("  <body>\n");
+    push_outlines(&mut xml, &sources.iter().collect::<Vec<_>>(), 0);
+    xml.push_str("  </body>\n");
+    xml.push_str("</opml>\n");
+    xml
+}
+
+// Outlines for the sources at category depth `depth`: feeds first, then one folder per category
+fn push_outlines(xml: &mut String, sources: &[&FeedSource], depth: usize) {
+    let indent = "  ".repeat(depth + 2);
+    let mut folders: BTreeMap<&str, Vec<&FeedSource>> = BTreeMap::new();
+    for source in sources {
+        match source.categories.get(depth) {
+            Some(category) => folders.entry(category.as_str()).or_default().push(source),
+            None => {
+                let site_url = source
+                    .site_url
+                    .as_deref()
+                    .map(|url| format!(" htmlUrl=\"{}\"", escape_xml(url)))
+                    .unwrap_or_default();
+                xml.push_str(&format!(
+                    "{}<outline type=\"rss\" text=\"{}\" title=\"{}\" xmlUrl=\"{}\"{}/>\n",
+                    indent,
+                    escape_xml(&source.name),
+                    escape_xml(&source.name),
+                    escape_xml(&source.url),
+                    site_url
+                ));
+            }
+        }
+    }
+    for (category, sources) in folders {
+        xml.push_str(&format!("{}<outline text=\"{}\">\n", indent, escape_xml(category)));
+        push_outlines(xml, &sources, depth + 1);
+        xml.push_str(&format!("{}</outline>\n", indent));
+    }
+}
+
+// One <item> or <entry> of a fetched feed, before it becomes an article
+#[derive(Debug, Default)]
+struct FeedItem {
+    title: String,
+    link: String,
+    guid: String,
+    published: String,
+    updated: String,
+    summary: String,
+    content: String,
+    author: String,
+    categories: Vec<String>,
+    image: Option<String>,
+}
+
+// Items of an RSS 2.0 or Atom document
+fn parse_feed(xml: &str) -> Result<Vec<FeedItem>, String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut items = Vec::new();
+    let mut item: Option<FeedItem> = None;
+    // Local names of the open elements, innermost last
+    let mut path: Vec<String> = Vec::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid feed: {}", e))?;
+        let text = match &event {
+            Event::Start(element) | Event::Empty(element) => {
+                let name = String::from_utf8_lossy(element.local_name().as_ref()).to_string();
+                if name == "item" || name == "entry" {
+                    item = Some(FeedItem::default());
+                }
+                if let Some(item) = item.as_mut() {
+                    let attributes = attributes(element);
+                    match name.as_str() {
+                        // Atom links live in attributes; prefer rel="alternate", the default
+                        "link" if attributes.contains_key("href") => {
+                            let rel = attributes.get("rel").map(String::as_str).unwrap_or("alternate");
+                            if rel == "alternate" && item.link.is_empty() {
+                                item.link = attributes["href"].clone();
+                            }
+                        }
+                        "category" => item.categories.extend(attributes.get("term").cloned()),
+                        "enclosure" | "content" | "thumbnail" if item.image.is_none() => {
+                            let is_image = attributes.get("type").is_some_and(|t| t.starts_with("image/"))
+                                || attributes.get("medium").is_some_and(|m| m == "image")
+                                || name == "thumbnail";
+                            if is_image {
+                                item.image = attributes.get("url").cloned();
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                if matches!(event, Event::Start(_)) {
+                    path.push(name);
+                }
+                continue;
+            }
+            Event::End(_) => {
+                if let Some(name) = path.pop() {
+                    if name == "item" || name == "entry" {
+                        items.extend(item.take());
+                    }
+                }
+                continue;
+            }
+            Event::Text(text) => text.unescape().map(|text| text.to_string()).unwrap_or_default(),
+            Event::CData(data) => String::from_utf8_lossy(data.as_ref()).to_string(),
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        let Some(item) = item.as_mut() else { continue };
+        let parent = path.len().checked_sub(2).and_then(|i| path.get(i)).map(String::as_str);
+        let field = match path.last().map(String::as_str) {
+            Some("title") => &mut item.title,
+            Some("link") => &mut item.link,
+            Some("guid") | Some("id") => &mut item.guid,
+            Some("pubDate") | Some("published") | Some("date") => &mut item.published,
+            Some("updated") => &mut item.updated,
+            Some("description") | Some("summary") => &mut item.summary,
+            // content:encoded in RSS, <content> in Atom
+            Some("encoded") | Some("content") => &mut item.content,
+            Some("creator") | Some("author") => &mut item.author,
+            Some("name") if parent == Some("author") => &mut item.author,
+            Some("category") => {
+                item.categories.push(text.trim().to_string());
+                continue;
+            }
+            _ => continue,
+        };
+        field.push_str(&text);
+    }
+
+    Ok(items)
+}
+
+// RSS uses RFC 2822 dates, Atom RFC 3339
+fn parse_feed_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+        .or_else(|| parse_timestamp(value))
+}
+
+// Plain-text excerpt of an HTML summary, cut at a character boundary
+fn excerpt(html: &str) -> String {
+    let text = strip_html(html).split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(EXCERPT_LENGTH) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text,
+    }
+}
+
+// One client for every feed, so a slow or stalled server cannot hold a refresh forever
+fn client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        Client::builder()
+            .timeout(Duration::from_secs(15))
+            .user_agent("Mozilla/5.0")
+            .build()
+            .expect("failed to build the feed HTTP client")
+    })
+}
+
+// Read a response body, giving up once it grows past MAX_FEED_BYTES
+async fn read_capped(mut response: reqwest::Response) -> Result<String, String> {
+    if response.content_length().is_some_and(|length| length > MAX_FEED_BYTES as u64) {
+        return Err(format!("larger than {} bytes", MAX_FEED_BYTES));
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if body.len() + chunk.len() > MAX_FEED_BYTES {
+            return Err(format!("larger than {} bytes", MAX_FEED_BYTES));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+// Fetch one feed source and turn its newest items into articles
+pub async fn fetch_feed(source: FeedSource) -> Vec<BlogArticle> {
+    let response = client().get(&source.url).send().await.and_then(|response| response.error_for_status());
+    let xml = match response {
+        Ok(response) => match read_capped(response).await {
+            Ok(xml) => xml,
+            Err(e) => {
+                eprintln!("Failed to read feed {}: {}", source.name, e);
+                return Vec::new();
+            }
+        },
+        Err(e) => {
+            eprintln!("Failed to fetch feed {}: {}", source.name, e);
+            return Vec::new();
+        }
+    };
+
+    let items = match parse_feed(&xml) {
+        Ok(items) => items,
+        Err(e) => {
+            eprintln!("Failed to parse feed {}: {}", source.name, e);
+            return Vec::new();
+        }
+    };
+
+    let feed_category = source.categories.first().map(|category| ids::slugify(category)).filter(|c| !c.is_empty());
+    items
+        .into_iter()
+        .filter(|item| !item.link.trim().is_empty())
+        .take(ITEMS_PER_FEED)
+        .map(|item| {
+            let url = item.link.trim().to_string();
+            let summary = if item.summary.is_empty() { &item.content } else { &item.summary };
+            let mut tags: Vec<String> = Vec::new();
+            for tag in item.categories.iter().chain(&source.categories).map(|c| ids::slugify(c)) {
+                if !tag.is_empty() && !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            BlogArticle {
+                id: ids::article_id(&source.name, Some(&item.guid), &url, &item.title),
+                title: strip_html(&item.title).trim().to_string(),
+                excerpt: excerpt(summary),
+                tags,
+                keywords: Vec::new(),
+                content: if item.content.is_empty() { item.summary.clone() } else { item.content.clone() },
+                description: None,
+                category: None,
+                categories: Vec::new(),
+                manual_category: None,
+                feed_category: feed_category.clone(),
+                enriched_at: None,
+                source: source.name.clone(),
+                sources: vec![source.name.clone()],
+                published_at: parse_feed_date(&item.published).or_else(|| parse_feed_date(&item.updated)),
+                fetched_at: Utc::now(),
+                author: Some(item.author.trim().to_string()).filter(|author| !author.is_empty()),
+                image_url: item.image,
+                score: None,
+                comment_count: None,
+                language: None,
+                extra: HashMap::new(),
+                url,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(name: &str, url: &str, categories: &[&str]) -> FeedSource {
+        FeedSource {
+            name: name.to_string(),
+            url: url.to_string(),
+            site_url: None,
+            categories: categories.iter().map(|category| category.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn opml_round_trips() {
+        let mut sources = vec![
+            source("The Rust Blog", "https://blog.rust-lang.org/feed.xml", &["Tech", "Rust"]),
+            source("Q&A <weekly>", "https://example.com/feed?a=1&b=\"2\"", &["Tech"]),
+            source("Loose", "https://example.org/rss", &[]),
+        ];
+        sources[0].site_url = Some("https://blog.rust-lang.org/".to_string());
+
+        let mut parsed = parse_opml(&opml(&sources, "Test")).unwrap();
+        parsed.sort_by(|a, b| a.url.cmp(&b.url));
+        sources.sort_by(|a, b| a.url.cmp(&b.url));
+        assert_eq!(parsed, sources);
+    }
+
+    #[test]
+    fn opml_category_attribute_adds_to_folders() {
+        let xml = r#"<opml version="2.0"><body>
+            <outline text="Tech">
+                <outline text="Blog" xmlUrl="https://example.com/feed" category="/Rust/Async,Tech"/>
+            </outline>
+        </body></opml>"#;
+        let parsed = parse_opml(xml).unwrap();
+        assert_eq!(parsed, vec![source("Blog", "https://example.com/feed", &["Tech", "Rust", "Async"])]);
+    }
+
+    #[test]
+    fn other_xml_is_not_opml() {
+        assert!(parse_opml("<rss><channel/></rss>").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+
+use crate::dedup;
+use crate::json_file::JsonFile;
+use crate::BlogArticle;
+
+// Articles kept between refreshes, keyed by ID and persisted as JSON.
+// Cloning is cheap: clones share the same underlying map.
+#[derive(Clone)]
+pub struct ArticleStore {
+    articles: Arc<RwLock<HashMap<String, BlogArticle>>>,
+    file: JsonFile<Vec<BlogArticle>>,
+}
+
+impl ArticleStore {
+    // Load the store from disk, starting empty if the file is missing or unreadable
+    pub fn open(path: PathBuf) -> Self {
+        let file: JsonFile<Vec<BlogArticle>> = JsonFile::new(path, "article store");
+        let articles = file.load().unwrap_or_default().into_iter().map(|a| (a.id.clone(), a)).collect();
+
+        ArticleStore {
+            articles: Arc::new(RwLock::new(articles)),
+            file,
+        }
+    }
+
+    // Insert new articles and fold re-fetched ones into their stored copy, returning the IDs
+    // that were not stored before. An article is already stored when its ID or canonical URL
+    // matches, so a story seen later on another source adds a source rather than a second entry.
+    // The stored copy keeps its fetch time, enrichment and anything a reader set.
+    pub fn upsert(&self, articles: Vec<BlogArticle>) -> Vec<String> {
+        let mut stored = self.articles.write().unwrap();
+        let mut by_url: HashMap<String, String> = stored
+            .values()
+            .filter_map(|article| dedup::canonical_url(&article.url).map(|key| (key, article.id.clone())))
+            .collect();
+        let mut new_ids = Vec::new();
+
+        for article in articles {
+            let key = dedup::canonical_url(&article.url);
+            let existing = if stored.contains_key(&article.id) {
+                Some(article.id.clone())
+            } else {
+                key.as_ref().and_then(|key| by_url.get(key).cloned())
+            };
+            match existing.and_then(|id| stored.get_mut(&id)) {
+                Some(existing) => dedup::merge_into(existing, article),
+                None => {
+                    if let Some(key) = key {
+                        by_url.insert(key, article.id.clone());
+                    }
+                    new_ids.push(article.id.clone());
+                    stored.insert(article.id.clone(), article);
+                }
+            }
+        }
+
+        new_ids
+    }
+
+    // Drop articles first fetched before `cutoff`, returning their IDs
+    pub fn prune(&self, cutoff: DateTime<Utc>) -> Vec<String> {
+        let mut stored = self.articles.write().unwrap();
+        let expired: Vec<String> = stored
+            .values()
+            .filter(|article| article.fetched_at < cutoff)
+            .map(|article| article.id.clone())
+            .collect();
+        for id in &expired {
+            stored.remove(id);
+        }
+        expired
+    }
+
+    // Apply a change to every stored article in place
+    pub fn update_all(&self, mut update: impl FnMut(&mut BlogArticle)) {
+        for article in self.articles.write().unwrap().values_mut() {
+            update(article);
+        }
+    }
+
+    // Change one article in place, returning the updated copy if it exists
+    pub fn update(&self, id: &str, update: impl FnOnce(&mut BlogArticle)) -> Option<BlogArticle> {
+        let mut stored = self.articles.write().unwrap();
+        let article = stored.get_mut(id)?;
+        update(article);
+        Some(article.clone())
+    }
+
+    pub fn get(&self, id: &str) -> Option<BlogArticle> {
+        self.articles.read().unwrap().get(id).cloned()
+    }
+
+    // All stored articles, newest first
+    pub fn all(&self) -> Vec<BlogArticle> {
+        let mut articles: Vec<BlogArticle> = self.articles.read().unwrap().values().cloned().collect();
+        articles.sort_by(|a, b| {
+            let a_time = a.published_at.unwrap_or(a.fetched_at);
+            let b_time = b.published_at.unwrap_or(b.fetched_at);
+            b_time.cmp(&a_time).then_with(|| a.id.cmp(&b.id))
+        });
+        articles
+    }
+
+    // Write the store to disk, replacing the previous file atomically
+    pub fn save(&self) {
+        self.file.save(&self.all());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_article;
+
+    #[test]
+    fn refetch_keeps_the_stored_copy() {
+        let store = ArticleStore::open(PathBuf::from("/nonexistent/articles.json"));
+        let first = test_article("hacker-news:1", "Rust 2.0 released");
+        let fetched_at = first.fetched_at;
+        assert_eq!(store.upsert(vec![first]), vec!["hacker-news:1".to_string()]);
+        store.update("hacker-news:1", |article| article.manual_category = Some("security".to_string()));
+
+        store.update("hacker-news:1", |article| {
+            article.description = Some("From the page's <head>".to_string());
+            article.enriched_at = Some(Utc::now());
+        });
+
+        let mut refetched = test_article("hacker-news:1", "Rust 2.0 released");
+        refetched.fetched_at = fetched_at + chrono::Duration::hours(1);
+        refetched.content = "The full announcement, longer than the title".to_string();
+        assert!(store.upsert(vec![refetched]).is_empty());
+
+        let stored = store.get("hacker-news:1").unwrap();
+        assert_eq!(stored.content, "The full announcement, longer than the title");
+        assert_eq!(stored.description.as_deref(), Some("From the page's <head>"));
+        assert!(stored.enriched_at.is_some());
+        assert_eq!(stored.manual_category.as_deref(), Some("security"));
+        assert_eq!(stored.fetched_at, fetched_at);
+    }
+
+    #[test]
+    fn prune_drops_articles_fetched_before_the_cutoff() {
+        let store = ArticleStore::open(PathBuf::from("/nonexistent/articles.json"));
+        let mut old = test_article("medium:1", "Old news");
+        old.fetched_at = Utc::now() - chrono::Duration::days(40);
+        store.upsert(vec![old, test_article("medium:2", "Fresh news")]);
+
+        assert_eq!(store.prune(Utc::now() - chrono::Duration::days(30)), vec!["medium:1".to_string()]);
+        assert!(store.get("medium:1").is_none());
+        assert!(store.get("medium:2").is_some());
+    }
+
+    #[test]
+    fn same_story_from_another_source_is_merged() {
+        let store = ArticleStore::open(PathBuf::from("/nonexistent/articles.json"));
+        let mut first = test_article("hacker-news:1", "Rust 2.0 released");
+        first.url = "https://blog.rust-lang.org/2026/rust-2.html".to_string();
+        store.upsert(vec![first]);
+
+        let mut later = test_article("dev-to:7", "Rust 2.0 released");
+        later.url = "https://www.blog.rust-lang.org/2026/rust-2.html/?utm_source=devto".to_string();
+        assert!(store.upsert(vec![later]).is_empty());
+
+        assert_eq!(store.all().len(), 1);
+        assert_eq!(store.get("hacker-news:1").unwrap().sources, vec!["hacker-news", "dev-to"]);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+
+use crate::ids::{permalink, slugify};
+use crate::BlogArticle;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion {
+    pub text: String,
+    // "title", "tag" or "source"
+    pub kind: &'static str,
+    pub url: String,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    // Lowercased text that queries are matched against
+    key: String,
+    // How many articles back the entry, so popular completions come first
+    weight: usize,
+    suggestion: Suggestion,
+}
+
+// Completions for search-as-you-type, rebuilt from the store on every refresh.
+// Cloning is cheap: clones share the same entries.
+#[derive(Debug, Clone, Default)]
+pub struct Suggester {
+    // Sorted by key so a prefix is a contiguous range
+    entries: Arc<RwLock<Vec<Entry>>>,
+}
+
+// Typos tolerated for a query of the given length; short prefixes must match exactly
+fn max_typos(length: usize) -> usize {
+    match length {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+// Edit distance between `query` and the closest prefix of `key`, counting a swap of
+// neighbouring letters as one typo, e.g. "rsut" is 1 from "rust 2.0"
+fn prefix_distance(query: &[char], key: &str) -> usize {
+    // Prefixes much longer than the query cannot be closer than the shorter ones
+    let key: Vec<char> = key.chars().take(query.len() * 2).collect();
+    // rows[i][j]: distance between query[..i] and key[..j]
+    let mut rows: Vec<Vec<usize>> = vec![(0..=key.len()).collect()];
+    for i in 1..=query.len() {
+        let mut row = vec![i; key.len() + 1];
+        for j in 1..=key.len() {
+            let cost = usize::from(query[i - 1] != key[j - 1]);
+            row[j] = (rows[i - 1][j - 1] + cost).min(rows[i - 1][j] + 1).min(row[j - 1] + 1);
+            if i > 1 && j > 1 && query[i - 1] == key[j - 2] && query[i - 2] == key[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[query.len()].iter().copied().min().unwrap_or(0)
+}
+
+impl Suggester {
+    pub fn new(articles: &[BlogArticle]) -> Self {
+        let suggester = Suggester::default();
+        suggester.rebuild(articles);
+        suggester
+    }
+
+    // Replace every entry with ones for the given articles
+    pub fn rebuild(&self, articles: &[BlogArticle]) {
+        let mut unique: HashMap<(String, &'static str), Entry> = HashMap::new();
+        let mut add = |text: &str, kind: &'static str, url: String| {
+            let key = text.trim().to_lowercase();
+            if key.is_empty() {
+                return;
+            }
+            unique
+                .entry((key.clone(), kind))
+                .or_insert_with(|| Entry {
+                    key,
+                    weight: 0,
+                    suggestion: Suggestion { text: text.trim().to_string(), kind, url },
+                })
+                .weight += 1;
+        };
+
+        for article in articles {
+            add(&article.title, "title", permalink(&article.id));
+            for tag in &article.tags {
+                add(tag, "tag", format!("/tag/{}", slugify(tag)));
+            }
+            for source in &article.sources {
+                add(source, "source", format!("/source/{}", slugify(source)));
+            }
+        }
+        let mut entries: Vec<Entry> = unique.into_values().collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        *self.entries.write().unwrap() = entries;
+    }
+
+    // Completions for a partially typed query: exact prefix matches first, then close misspellings
+    pub fn suggest(&self, query: &str, limit: usize) -> Vec<Suggestion> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+        let entries = self.entries.read().unwrap();
+
+        let start = entries.partition_point(|entry| entry.key.as_str() < query.as_str());
+        let mut prefixed: Vec<&Entry> = entries[start..]
+            .iter()
+            .take_while(|entry| entry.key.starts_with(&query))
+            .collect();
+        prefixed.sort_by(|a, b| b.weight.cmp(&a.weight).then_with(|| a.key.len().cmp(&b.key.len())));
+
+        let mut suggestions: Vec<Suggestion> = prefixed.iter().take(limit).map(|e| e.suggestion.clone()).collect();
+        let typos = max_typos(query.chars().count());
+        if suggestions.len() < limit && typos > 0 {
+            let chars: Vec<char> = query.chars().collect();
+            let mut close: Vec<(usize, &Entry)> = entries
+                .iter()
+                .filter(|entry| !entry.key.starts_with(&query))
+                .map(|entry| (prefix_distance(&chars, &entry.key), entry))
+                .filter(|(distance, _)| *distance <= typos)
+                .collect();
+            close.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.weight.cmp(&a.1.weight)));
+            suggestions.extend(close.into_iter().take(limit - suggestions.len()).map(|(_, e)| e.suggestion.clone()));
+        }
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_article;
+
+    fn distance(query: &str, key: &str) -> usize {
+        prefix_distance(&query.chars().collect::<Vec<_>>(), key)
+    }
+
+    #[test]
+    fn prefix_distance_counts_typos_against_the_closest_prefix() {
+        assert_eq!(distance("rust", "rust 2.0 released"), 0);
+        assert_eq!(distance("rsut", "rust 2.0 released"), 1);
+        assert_eq!(distance("rist", "rust 2.0 released"), 1);
+        assert_eq!(distance("rst", "rust 2.0 released"), 1);
+        assert_eq!(distance("ruust", "rust 2.0 released"), 1);
+        assert!(distance("postgres", "rust 2.0 released") > max_typos(8));
+        assert_eq!(distance("", "rust"), 0);
+    }
+
+    #[test]
+    fn prefix_matches_come_before_misspellings() {
+        let mut rust = test_article("hacker-news:1", "Rust 2.0 released");
+        rust.tags = vec!["Rust".to_string()];
+        let mut ruby = test_article("hacker-news:2", "Ruby on Rails 8");
+        ruby.tags = vec!["Rust".to_string()];
+        let suggester = Suggester::new(&[rust, ruby]);
+
+        let texts = |query: &str| -> Vec<String> { suggester.suggest(query, 5).into_iter().map(|s| s.text).collect() };
+        // The tag backs two articles, so it outranks the title
+        assert_eq!(texts("Rust"), ["Rust", "Rust 2.0 released"]);
+        assert_eq!(texts("rsut"), ["Rust", "Rust 2.0 released"]);
+        // Three letters must match exactly
+        assert_eq!(texts("rsu"), Vec::<String>::new());
+        assert_eq!(suggester.suggest("rust", 1).len(), 1);
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Deserialize;
+
+use crate::ids::slugify;
+
+// A tag in the hierarchy, e.g. "rust" with parent "programming" and alias "rustlang"
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TagDefinition {
+    pub name: String,
+    pub parent: Option<String>,
+    // Spellings that mean the same thing and are rewritten to `name`
+    pub aliases: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TaxonomyConfig {
+    pub tags: Vec<TagDefinition>,
+    // Retired tags folded into another one, e.g. "golang" = "go"
+    pub merges: HashMap<String, String>,
+}
+
+// Resolves raw tags to canonical ones and answers hierarchy questions
+#[derive(Debug, Clone, Default)]
+pub struct Taxonomy {
+    // Alias or merged tag -> replacement
+    renames: HashMap<String, String>,
+    // Canonical tag -> parent
+    parents: HashMap<String, String>,
+    // Every tag named in the configuration
+    defined: HashSet<String>,
+}
+
+// Upper bound on rename and parent chains, so a misconfigured cycle cannot hang
+const MAX_DEPTH: usize = 16;
+
+impl Taxonomy {
+    pub fn new(config: &TaxonomyConfig) -> Self {
+        let mut taxonomy = Taxonomy::default();
+
+        for (from, to) in &config.merges {
+            taxonomy.renames.insert(slugify(from), slugify(to));
+            taxonomy.defined.insert(slugify(to));
+        }
+        for tag in &config.tags {
+            let name = slugify(&tag.name);
+            taxonomy.defined.insert(name.clone());
+            for alias in &tag.aliases {
+                taxonomy.renames.insert(slugify(alias), name.clone());
+            }
+            if let Some(parent) = &tag.parent {
+                taxonomy.parents.insert(name.clone(), slugify(parent));
+            }
+        }
+
+        // Parents may themselves be written as aliases
+        let parents: Vec<(String, String)> = taxonomy.parents.drain().collect();
+        for (child, parent) in parents {
+            let parent = taxonomy.canonical(&parent);
+            taxonomy.parents.insert(child, parent);
+        }
+        taxonomy
+    }
+
+    // Canonical form of a tag: slugified, with aliases and merges followed
+    pub fn canonical(&self, tag: &str) -> String {
+        let mut tag = slugify(tag);
+        for _ in 0..MAX_DEPTH {
+            match self.renames.get(&tag) {
+                Some(next) if *next != tag => tag = next.clone(),
+                _ => break,
+            }
+        }
+        tag
+    }
+
+    // Canonicalize a list of tags, dropping empties and duplicates but keeping order
+    pub fn normalize(&self, tags: &[String]) -> Vec<String> {
+        let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = self.canonical(tag);
+            if !tag.is_empty() && !normalized.contains(&tag) {
+                normalized.push(tag);
+            }
+        }
+        normalized
+    }
+
+    pub fn parent(&self, tag: &str) -> Option<&str> {
+        self.parents.get(tag).map(String::as_str)
+    }
+
+    // True when `tag` is `ancestor` or sits anywhere below it
+    pub fn is_within(&self, tag: &str, ancestor: &str) -> bool {
+        let mut current = tag;
+        for _ in 0..MAX_DEPTH {
+            if current == ancestor {
+                return true;
+            }
+            match self.parent(current) {
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    // Tags directly below `tag` in the hierarchy
+    pub fn children(&self, tag: &str) -> Vec<String> {
+        let mut children: Vec<String> = self
+            .parents
+            .iter()
+            .filter(|(_, parent)| parent.as_str() == tag)
+            .map(|(child, _)| child.clone())
+            .collect();
+        children.sort();
+        children
+    }
+
+    // Whether the tag is part of the configured hierarchy at all
+    pub fn is_defined(&self, tag: &str) -> bool {
+        self.defined.contains(tag) || self.parents.values().any(|parent| parent == tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn taxonomy() -> Taxonomy {
+        let tag = |name: &str, parent: Option<&str>, aliases: &[&str]| TagDefinition {
+            name: name.to_string(),
+            parent: parent.map(str::to_string),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+        };
+        Taxonomy::new(&TaxonomyConfig {
+            tags: vec![
+                tag("programming", None, &["coding"]),
+                // Parents written as aliases resolve to the canonical tag
+                tag("rust", Some("Coding"), &["rustlang", "Rust Lang"]),
+                tag("go", Some("programming"), &[]),
+            ],
+            merges: HashMap::from([("golang".to_string(), "go".to_string())]),
+        })
+    }
+
+    #[test]
+    fn aliases_and_merges_resolve_to_the_canonical_tag() {
+        let taxonomy = taxonomy();
+        assert_eq!(taxonomy.canonical("RustLang"), "rust");
+        assert_eq!(taxonomy.canonical("Rust Lang"), "rust");
+        assert_eq!(taxonomy.canonical("golang"), "go");
+        assert_eq!(taxonomy.canonical("Databases"), "databases");
+
+        let tags = ["rustlang", "rust", "Golang", " ", "databases"].map(String::from);
+        assert_eq!(taxonomy.normalize(&tags), ["rust", "go", "databases"]);
+    }
+
+    #[test]
+    fn parents_form_the_hierarchy() {
+        let taxonomy = taxonomy();
+        assert_eq!(taxonomy.parent("rust"), Some("programming"));
+        assert_eq!(taxonomy.children("programming"), ["go", "rust"]);
+        assert!(taxonomy.is_within("rust", "programming"));
+        assert!(taxonomy.is_within("rust", "rust"));
+        assert!(!taxonomy.is_within("programming", "rust"));
+        assert!(taxonomy.is_defined("go"));
+        assert!(!taxonomy.is_defined("databases"));
+    }
+
+    #[test]
+    fn cyclic_merges_terminate() {
+        let taxonomy = Taxonomy::new(&TaxonomyConfig {
+            merges: HashMap::from([("a".to_string(), "b".to_string()), ("b".to_string(), "a".to_string())]),
+            ..TaxonomyConfig::default()
+        });
+        assert!(["a", "b"].contains(&taxonomy.canonical("a").as_str()));
+    }
+}
@@ -0,0 +1,75 @@
+// Words that carry no signal about what an article is about
+pub const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been",
+    "but", "by", "can", "could", "did", "do", "does", "for", "from", "had", "has", "have", "he",
+    "her", "his", "how", "i", "if", "in", "into", "is", "it", "its", "just", "more", "most",
+    "my", "new", "no", "not", "now", "of", "on", "one", "or", "our", "out", "over", "she", "so",
+    "some", "than", "that", "the", "their", "them", "then", "there", "these", "they", "this",
+    "to", "up", "us", "was", "we", "were", "what", "when", "which", "who", "why", "will",
+    "with", "would", "you", "your",
+];
+
+pub fn is_stopword(word: &str) -> bool {
+    STOPWORDS.contains(&word)
+}
+
+// Every lowercased word, in order, stopwords included
+pub fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+// Lowercased words of two or more characters, without stopwords
+pub fn tokenize(text: &str) -> Vec<String> {
+    words(text)
+        .into_iter()
+        .filter(|word| word.chars().count() > 1 && !is_stopword(word))
+        .collect()
+}
+
+// Render Markdown, as Dev.to ships it, to HTML. Plain text passes through as paragraphs.
+// Sources embed raw HTML, so the result is sanitized: scripts, styles and event handlers are
+// dropped before it reaches a page, a feed or the API.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, pulldown_cmark::Parser::new(markdown));
+    ammonia::clean(&html)
+}
+
+// Drop HTML tags from scraped markup, keeping the text between them
+pub fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_html_is_sanitized() {
+        let html = markdown_to_html(
+            "# Title\n\nSome *text* <script>alert(1)</script>\n\n<img src=\"https://example.com/a.png\" onerror=\"alert(2)\">\n\n<a href=\"javascript:alert(3)\">link</a>",
+        );
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<em>text</em>"));
+        assert!(html.contains("src=\"https://example.com/a.png\""));
+        assert!(!html.contains("script"));
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("javascript:"));
+    }
+}
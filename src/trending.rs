@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::BlogArticle;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TrendingConfig {
+    // Length of the window whose activity is being judged
+    pub recent_hours: i64,
+    // Length of the window before it that defines "normal"
+    pub baseline_hours: i64,
+    // Topics mentioned fewer times than this recently are ignored
+    pub min_recent_count: usize,
+    // Topics listed at most
+    pub limit: usize,
+}
+
+impl Default for TrendingConfig {
+    fn default() -> Self {
+        TrendingConfig {
+            recent_hours: 24,
+            baseline_hours: 24 * 7,
+            min_recent_count: 2,
+            limit: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendingArticle {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendingTopic {
+    pub topic: String,
+    // "tag" for source tags, "keyword" for extracted keywords
+    pub kind: &'static str,
+    pub recent_count: usize,
+    pub baseline_count: usize,
+    pub score: f64,
+    // Recent articles mentioning the topic
+    pub articles: Vec<TrendingArticle>,
+}
+
+#[derive(Default)]
+struct Counts<'a> {
+    recent: Vec<&'a BlogArticle>,
+    baseline: usize,
+}
+
+// Topics whose recent frequency bursts above their baseline rate, strongest first
+pub fn rising_topics(articles: &[BlogArticle], config: &TrendingConfig, now: DateTime<Utc>) -> Vec<TrendingTopic> {
+    let recent_start = now - Duration::hours(config.recent_hours);
+    let baseline_start = recent_start - Duration::hours(config.baseline_hours);
+
+    let mut topics: HashMap<(String, &'static str), Counts> = HashMap::new();
+    for article in articles {
+        let seen_at = article.published_at.unwrap_or(article.fetched_at);
+        if seen_at < baseline_start || seen_at > now {
+            continue;
+        }
+        let recent = seen_at >= recent_start;
+
+        let terms = article
+            .tags
+            .iter()
+            .map(|tag| (tag.clone(), "tag"))
+            .chain(article.keywords.iter().map(|keyword| (keyword.clone(), "keyword")));
+        for key in terms {
+            let counts = topics.entry(key).or_default();
+            if recent {
+                counts.recent.push(article);
+            } else {
+                counts.baseline += 1;
+            }
+        }
+    }
+
+    // Scale the baseline to the recent window's length, then measure how far the recent
+    // count sits above that expectation in Poisson standard deviations
+    let window_ratio = config.recent_hours as f64 / config.baseline_hours.max(1) as f64;
+    let mut rising: Vec<TrendingTopic> = topics
+        .into_iter()
+        .filter(|(_, counts)| counts.recent.len() >= config.min_recent_count.max(1))
+        .filter_map(|((topic, kind), counts)| {
+            let expected = counts.baseline as f64 * window_ratio;
+            let score = (counts.recent.len() as f64 - expected) / (expected + 1.0).sqrt();
+            (score > 0.0).then(|| TrendingTopic {
+                topic,
+                kind,
+                recent_count: counts.recent.len(),
+                baseline_count: counts.baseline,
+                score,
+                articles: counts
+                    .recent
+                    .iter()
+                    .map(|article| TrendingArticle {
+                        id: article.id.clone(),
+                        title: article.title.clone(),
+                        url: article.url.clone(),
+                        source: article.source.clone(),
+                    })
+                    .collect(),
+            })
+        })
+        .collect();
+
+    rising.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.topic.cmp(&b.topic)));
+    rising.truncate(config.limit);
+    rising
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_article;
+
+    fn seen(now: DateTime<Utc>, hours_ago: i64, tags: &[&str], count: usize) -> Vec<BlogArticle> {
+        (0..count)
+            .map(|i| {
+                let mut article = test_article(&format!("medium:{}-{}-{}", tags.join("-"), hours_ago, i), "Title");
+                article.tags = tags.iter().map(|tag| tag.to_string()).collect();
+                article.published_at = Some(now - Duration::hours(hours_ago));
+                article
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bursts_above_the_baseline_rate_rank_first() {
+        let now = Utc::now();
+        let config = TrendingConfig { recent_hours: 24, baseline_hours: 96, min_recent_count: 2, limit: 10 };
+        let mut articles = Vec::new();
+        // Expected 0 recent mentions, seen 3
+        articles.extend(seen(now, 2, &["wasm"], 3));
+        // Expected 8 * 24/96 = 2, seen 4
+        articles.extend(seen(now, 3, &["rust"], 4));
+        articles.extend(seen(now, 48, &["rust"], 8));
+        // Expected 3, seen 2: steady, not rising
+        articles.extend(seen(now, 5, &["python"], 2));
+        articles.extend(seen(now, 60, &["python"], 12));
+        // Too few recent mentions, or outside the windows altogether
+        articles.extend(seen(now, 1, &["zig"], 1));
+        articles.extend(seen(now, 24 * 30, &["zig"], 1));
+        articles.extend(seen(now, -5, &["zig"], 1));
+        let mut keyword = test_article("medium:keyword", "Title");
+        keyword.keywords = vec!["wasm".to_string()];
+        keyword.published_at = Some(now - Duration::hours(1));
+        articles.push(keyword.clone());
+        articles.push(BlogArticle { id: "medium:keyword-2".to_string(), ..keyword });
+
+        let rising = rising_topics(&articles, &config, now);
+        let topics: Vec<(&str, &str, usize, usize)> =
+            rising.iter().map(|t| (t.topic.as_str(), t.kind, t.recent_count, t.baseline_count)).collect();
+        assert_eq!(topics, [("wasm", "tag", 3, 0), ("wasm", "keyword", 2, 0), ("rust", "tag", 4, 8)]);
+        assert!((rising[0].score - 3.0).abs() < 1e-9);
+        assert!((rising[2].score - 2.0 / 3f64.sqrt()).abs() < 1e-9);
+        assert_eq!(rising[0].articles.len(), 3);
+
+        let limited = rising_topics(&articles, &TrendingConfig { limit: 1, ..config }, now);
+        assert_eq!(limited.len(), 1);
+    }
+}
@@ -0,0 +1,41 @@
+// static/js/suggest.js
+
+// Fill the navbar search box's datalist with completions while typing
+document.addEventListener('DOMContentLoaded', function() {
+    const input = document.querySelector('input[data-suggest]');
+    if (!input) {
+        return;
+    }
+    const list = document.getElementById(input.getAttribute('list'));
+    let timer = null;
+    let latest = '';
+
+    input.addEventListener('input', () => {
+        clearTimeout(timer);
+        timer = setTimeout(async () => {
+            const query = input.value.trim();
+            latest = query;
+            if (query.length === 0) {
+                list.replaceChildren();
+                return;
+            }
+
+            try {
+                const response = await fetch('/api/suggest?q=' + encodeURIComponent(query));
+                const suggestions = await response.json();
+                // Ignore answers to queries the user has already typed past
+                if (query !== latest) {
+                    return;
+                }
+                list.replaceChildren(...suggestions.map(suggestion => {
+                    const option = document.createElement('option');
+                    option.value = suggestion.text;
+                    option.label = suggestion.kind;
+                    return option;
+                }));
+            } catch (error) {
+                list.replaceChildren();
+            }
+        }, 100);
+    });
+});